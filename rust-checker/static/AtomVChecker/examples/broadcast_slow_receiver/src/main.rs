@@ -0,0 +1,26 @@
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+// A known-buggy fixture for `SlowReceiverDetector::detect_in_caller`: `rx1`
+// only gets to drain the ring buffer after acquiring `lock`, while its
+// sibling `rx2` (subscribed from the same sender) drains unlocked, so `rx1`
+// structurally falls behind -- and neither call site checks its `Result`
+// for `Lagged`, so an overwritten message is silently dropped instead of
+// reported.
+fn poll_siblings(lock: &Mutex<()>) {
+    let (tx, mut rx1) = broadcast::channel::<u32>(16);
+    let mut rx2 = tx.subscribe();
+    let _ = tx.send(1);
+
+    let _guard = lock.lock().unwrap();
+    let _ = rx1.try_recv();
+    drop(_guard);
+
+    let _ = rx2.try_recv();
+}
+
+#[tokio::main]
+async fn main() {
+    let lock = Mutex::new(());
+    poll_siblings(&lock);
+}
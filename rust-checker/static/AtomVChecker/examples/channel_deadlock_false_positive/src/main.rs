@@ -0,0 +1,23 @@
+// A must-not-fire regression case for
+// `ChannelDeadlockDetector::detect_cross_thread`: the spawned closure's
+// `recv` on `rx2` and the main thread's `recv` on `rx` share channel
+// identity with the other side's sends, but there's no real cycle --
+// main's `tx2.send(2)` always runs before main's own `rx.recv()`, so the
+// closure's wait on `rx2` is always cleared before main can itself
+// block. Lifted verbatim from
+// `rust-checker/static/lockbud/toys/channel-deadlock/src/main.rs`, which
+// is NOT actually buggy despite its directory name.
+use std::sync::mpsc::channel;
+use std::thread;
+
+fn main() {
+    let (tx, rx) = channel();
+    let (tx2, rx2) = channel();
+    let th = thread::spawn(move || {
+        let _ = rx2.recv().unwrap(); // 1. wait for tx2.send
+        let _ = tx.send(1).unwrap(); // 2. tx.send
+    });
+    let _ = tx2.send(2).unwrap(); // 4. tx2.send
+    let _ = rx.recv().unwrap(); // 3. wait for tx.send
+    th.join().unwrap();
+}
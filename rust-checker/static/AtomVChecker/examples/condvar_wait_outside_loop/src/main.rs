@@ -0,0 +1,29 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+// A known-buggy fixture for `CondvarDetector::detect_wait_not_in_loop`:
+// this `wait` call is guarded by an `if`, not a `while`, so a spurious
+// wakeup (or a `notify` that lands between the `lock()` and the `wait()`)
+// resumes this thread without re-checking that `ready` actually became
+// true.
+fn wait_for_ready(pair: &(Mutex<bool>, Condvar)) {
+    let (lock, cvar) = pair;
+    let mut ready = lock.lock().unwrap();
+    if !*ready {
+        ready = cvar.wait(ready).unwrap();
+    }
+    assert!(*ready);
+}
+
+fn main() {
+    let pair = Arc::new((Mutex::new(false), Condvar::new()));
+    let pair2 = Arc::clone(&pair);
+    let handle = thread::spawn(move || {
+        let (lock, cvar) = &*pair2;
+        let mut ready = lock.lock().unwrap();
+        *ready = true;
+        cvar.notify_one();
+    });
+    wait_for_ready(&pair);
+    handle.join().unwrap();
+}
@@ -0,0 +1,15 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// A known-buggy fixture for `detect_weak_cas_outside_loop`: this
+// `compare_exchange_weak` call isn't on any loop, so a spurious failure
+// here (allowed even when the comparison would have succeeded) silently
+// drops the update instead of being retried.
+fn set_if_zero(counter: &AtomicUsize, new_value: usize) -> bool {
+    counter.compare_exchange_weak(0, new_value, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+}
+
+fn main() {
+    let counter = AtomicUsize::new(0);
+    set_if_zero(&counter, 7);
+    println!("{}", counter.load(Ordering::SeqCst));
+}
@@ -0,0 +1,211 @@
+//! Cross-session disk cache for per-function `PointsToMap` results, so a
+//! repeat run over an unchanged `Body` can skip `Andersen::analyze`'s
+//! fixpoint solve instead of redoing it -- the in-memory cache
+//! `AliasAnalysis::pts` already avoids that within one process, but it
+//! starts empty on every new `rustc` invocation. Mirrors `crate::cache`'s
+//! per-crate report cache, scoped down to a single `DefId`.
+//!
+//! `ConstraintNode` embeds `PlaceRef`/`ConstantKind`, both borrowed out of
+//! the current `TyCtxt`'s arenas, so a node can't be serialized and handed
+//! back in a later process. Instead every node is lowered to a
+//! `LoweredNode`: places become `(local index, rendered projection path)`
+//! and constants become a hash of their own rendered text -- the same
+//! "parse what `{:?}` already gives us" trick
+//! `detector::atomic::diagnostic::Diagnostic::from_report` uses to recover
+//! a span without holding onto the original `Span`. A `LoweredNode` is
+//! re-lifted into a real `ConstraintNode<'tcx>` on load by re-running the
+//! (cheap) constraint *collection* pass over the current run's body and
+//! matching each freshly collected node against its lowered form -- only
+//! the expensive fixpoint solve is skipped on a cache hit, not the graph
+//! construction the solve would start from anyway.
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use fs4::FileExt;
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use rustc_middle::mir::visit::Visitor;
+use rustc_middle::mir::Body;
+use rustc_middle::ty::TyCtxt;
+use serde::{Deserialize, Serialize};
+
+use super::{ConstantKind, ConstraintGraphCollector, ConstraintNode, PlaceRef, PointsToMap};
+
+/// Fingerprint-stable, arena-free stand-in for a `ConstraintNode`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum LoweredNode {
+    Alloc(LoweredPlace),
+    Place(LoweredPlace),
+    Constant(u64),
+    ConstantDeref(u64),
+    Construct(LoweredPlace),
+    FunctionRet(LoweredPlace),
+    ParameterInto(LoweredPlace),
+    SmartPointer(LoweredPlace),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct LoweredPlace {
+    local: u32,
+    /// One rendered `{:?}` string per projection element (e.g. `"Field(1)"`,
+    /// `"Deref"`). Not meant to be parsed back into a `ProjectionElem` --
+    /// only ever compared for equality against a freshly collected node's
+    /// own rendering of the same projection.
+    projection: Vec<String>,
+}
+
+fn lower_place(place: PlaceRef<'_>) -> LoweredPlace {
+    LoweredPlace {
+        local: place.local.as_u32(),
+        projection: place.projection.iter().map(|elem| format!("{:?}", elem)).collect(),
+    }
+}
+
+fn hash_rendered(value: &ConstantKind<'_>) -> u64 {
+    let mut hasher = FxHasher::default();
+    format!("{:?}", value).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn lower_node(node: ConstraintNode<'_>) -> LoweredNode {
+    match node {
+        ConstraintNode::Alloc(p) => LoweredNode::Alloc(lower_place(p)),
+        ConstraintNode::Place(p) => LoweredNode::Place(lower_place(p)),
+        ConstraintNode::Constant(c) => LoweredNode::Constant(hash_rendered(&c)),
+        ConstraintNode::ConstantDeref(c) => LoweredNode::ConstantDeref(hash_rendered(&c)),
+        ConstraintNode::Construct(p) => LoweredNode::Construct(lower_place(p)),
+        ConstraintNode::FunctionRet(p) => LoweredNode::FunctionRet(lower_place(p)),
+        ConstraintNode::ParameterInto(p) => LoweredNode::ParameterInto(lower_place(p)),
+        ConstraintNode::SmartPointer(p) => LoweredNode::SmartPointer(lower_place(p)),
+    }
+}
+
+/// Stable hash of a `Body`'s own content -- statements, terminators, and
+/// local types -- analogous to rustc's incremental dep-node fingerprints
+/// and to `crate::cache::instance_fingerprint`'s per-function own-body
+/// hash. Unlike that one, this omits callees: Andersen's analysis is
+/// intra-procedural, so a function's points-to result never depends on a
+/// callee's body, only on its own MIR.
+///
+/// 128 bits, built from two independently-salted `FxHasher` passes over the
+/// same rendered text rather than pulling in `rustc_data_structures`'s own
+/// `Fingerprint`/`StableHasher` -- this crate's other fingerprints (here and
+/// in `crate::cache`) are already plain `FxHasher` hex strings, and a wider
+/// fingerprint only needs to shrink the collision odds of reusing a stale
+/// on-disk entry across unrelated bodies, not rustc's own stability
+/// guarantees.
+pub fn body_fingerprint(body: &Body<'_>) -> String {
+    let rendered = format!("{:#?}", body);
+
+    let mut low = FxHasher::default();
+    rendered.hash(&mut low);
+
+    let mut high = FxHasher::default();
+    // Salt so the second pass doesn't just repeat the first hasher's state.
+    0xa5a5_a5a5_a5a5_a5a5u64.hash(&mut high);
+    rendered.hash(&mut high);
+
+    format!("{:016x}{:016x}", low.finish(), high.finish())
+}
+
+type SerializedPointsTo = Vec<(LoweredNode, Vec<LoweredNode>)>;
+
+pub struct PointsToCache {
+    dir: PathBuf,
+}
+
+impl PointsToCache {
+    pub fn new(output_directory: &Path) -> Self {
+        Self {
+            dir: output_directory.join("atomvchecker-cache").join("pointsto"),
+        }
+    }
+
+    fn entry_path(&self, def_path: &str, fingerprint: &str) -> PathBuf {
+        let mut hasher = FxHasher::default();
+        def_path.hash(&mut hasher);
+        self.dir.join(format!("{:016x}-{}.json", hasher.finish(), fingerprint))
+    }
+
+    /// Load and re-lift a cached `PointsToMap`, or `None` on a cache miss:
+    /// no entry, an unreadable/corrupt one, or a `LoweredNode` that no
+    /// longer matches anything in the freshly collected graph (the body
+    /// fingerprint matched but, e.g., projection rendering changed between
+    /// `rustc` versions -- fall back to a full re-solve rather than hand
+    /// back a partial result).
+    pub fn load<'tcx>(&self, tcx: TyCtxt<'tcx>, body: &Body<'tcx>, def_path: &str, fingerprint: &str) -> Option<PointsToMap<'tcx>> {
+        let content = read_locked(&self.entry_path(def_path, fingerprint))?;
+        let serialized: SerializedPointsTo = serde_json::from_str(&content).ok()?;
+
+        // Re-collect (cheap: one pass building the constraint graph, no
+        // fixpoint solve) so every `LoweredNode` has a real, current
+        // `ConstraintNode<'tcx>` to re-lift against.
+        let mut collector = ConstraintGraphCollector::new(body, tcx);
+        collector.visit_body(body);
+        let graph = collector.finish();
+        let mut by_lowered: HashMap<LoweredNode, ConstraintNode<'tcx>> = HashMap::new();
+        for node in graph.nodes() {
+            by_lowered.insert(lower_node(node), node);
+        }
+
+        let mut result: PointsToMap<'tcx> = FxHashMap::default();
+        for (lowered_key, lowered_pointees) in serialized {
+            let key = *by_lowered.get(&lowered_key)?;
+            let mut pointees: FxHashSet<ConstraintNode<'tcx>> = FxHashSet::default();
+            for lowered_pointee in lowered_pointees {
+                pointees.insert(*by_lowered.get(&lowered_pointee)?);
+            }
+            result.insert(key, pointees);
+        }
+        Some(result)
+    }
+
+    pub fn store(&self, def_path: &str, fingerprint: &str, pts: &PointsToMap<'_>) {
+        if fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        let serialized: SerializedPointsTo = pts
+            .iter()
+            .map(|(node, pointees)| (lower_node(*node), pointees.iter().map(|p| lower_node(*p)).collect()))
+            .collect();
+        if let Ok(content) = serde_json::to_string(&serialized) {
+            write_locked(&self.entry_path(def_path, fingerprint), &content);
+        }
+    }
+}
+
+/// Read `path` back out from under an advisory shared lock, so a concurrent
+/// `write_locked` for the same entry (another `rustc` invocation racing on
+/// the same crate) can't be observed mid-write. Any failure -- missing
+/// entry, lock contention, non-UTF8 content -- is just a cache miss.
+fn read_locked(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    file.lock_shared().ok()?;
+    let content = fs::read_to_string(path).ok();
+    let _ = file.unlock();
+    content
+}
+
+/// Write `content` to `path` under an advisory exclusive lock, so two
+/// `rustc` invocations analyzing the same crate concurrently don't
+/// interleave their writes into one corrupt entry. Best-effort, same as the
+/// unlocked `fs::write` this replaces: a failure here is just a cache miss
+/// on the next run, not a hard error.
+fn write_locked(path: &Path, content: &str) {
+    let Ok(file) = fs::OpenOptions::new().create(true).write(true).open(path) else {
+        return;
+    };
+    if file.lock_exclusive().is_err() {
+        return;
+    }
+    // Truncate only once the lock is held, so a concurrent reader or writer
+    // never sees this entry emptied out from under it.
+    if file.set_len(0).is_err() {
+        let _ = file.unlock();
+        return;
+    }
+    use std::io::Write;
+    let _ = (&file).write_all(content.as_bytes());
+    let _ = file.unlock();
+}
@@ -11,10 +11,13 @@ extern crate rustc_hir;
 extern crate rustc_index;
 
 use std::collections::{VecDeque, HashSet};
+use std::sync::RwLock;
 
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use rustc_hir::def_id::DefId;
+use rustc_index::bit_set::{BitRelations, HybridBitSet};
+use rustc_index::vec::IndexVec;
 use rustc_middle::mir::visit::Visitor;
 use rustc_middle::mir::{
     Body, Constant, ConstantKind, Local, Location, Operand, Place, PlaceRef,
@@ -22,13 +25,16 @@ use rustc_middle::mir::{
 };
 use rustc_middle::ty::{TyCtxt, TyKind};
 
+use petgraph::algo::tarjan_scc;
 use petgraph::dot::{Config, Dot};
 use petgraph::graph::NodeIndex;
-use petgraph::visit::EdgeRef;
+use petgraph::visit::{EdgeFiltered, EdgeRef};
 use petgraph::{Directed, Direction, Graph};
 use crate::analysis::callgraph::InstanceId;
 use crate::interest::memory::ownership;
 
+pub mod cache;
+
 /// Field-sensitive intra-procedural Andersen pointer analysis.
 /// <https://helloworld.pub/program-analysis-andersen-pointer-analysis-algorithm-based-on-svf.html>
 /// 1. collect constraints from MIR to build a `ConstraintGraph`
@@ -44,25 +50,114 @@ use crate::interest::memory::ownership;
 pub struct Andersen<'a, 'tcx> {
     body: &'a Body<'tcx>,
     tcx: TyCtxt<'tcx>,
-    pts: PointsToMap<'tcx>,
+    /// Interns every `ConstraintNode` seen during solving to a dense
+    /// `NodeId`, so `pts` below can use `rustc_index`'s bitsets instead of
+    /// `FxHashSet<ConstraintNode>`. See `NodeInterner`.
+    interner: NodeInterner<'tcx>,
+    /// The working points-to relation, keyed and valued by interned ids.
+    /// `union_pts` becomes an in-place bitset OR instead of a clone +
+    /// extend of a whole hash set -- the dominant cost of the old
+    /// `FxHashMap<ConstraintNode, FxHashSet<ConstraintNode>>`
+    /// representation, since every worklist step used to rehash and clone
+    /// an entire set just to test/grow it by a handful of entries.
+    pts: InternedPointsTo,
+    /// Cross-session disk cache + this function's def-path key, set by
+    /// `new_with_cache`. `None` means `analyze` always re-solves, same as
+    /// before this existed.
+    cache: Option<(&'a cache::PointsToCache, String)>,
+    /// Populated by `analyze` on a cache hit; `finish` returns this
+    /// directly instead of decoding `pts`/`interner`, which never get
+    /// populated in that case.
+    cached_result: Option<PointsToMap<'tcx>>,
 }
 
 pub type PointsToMap<'tcx> = FxHashMap<ConstraintNode<'tcx>, FxHashSet<ConstraintNode<'tcx>>>;
 
+rustc_index::newtype_index! {
+    /// Dense id a `ConstraintNode` is interned to for the duration of one
+    /// `Andersen::analyze` run. Never persisted or compared across runs.
+    pub struct NodeId {
+        DEBUG_FORMAT = "n{}"
+    }
+}
+
+/// Bidirectional `ConstraintNode <-> NodeId` mapping. `ConstraintGraph`
+/// never introduces a brand new `ConstraintNode` value once
+/// `Andersen::analyze` starts folding in `add_alloc`/`add_constant` (later
+/// changes to the graph only merge existing nodes together, via
+/// `collapse_copy_cycles`), so every node worth a `NodeId` is known up
+/// front and the interner's domain only ever grows by construction, never
+/// needs renumbering.
+#[derive(Default)]
+struct NodeInterner<'tcx> {
+    nodes: IndexVec<NodeId, ConstraintNode<'tcx>>,
+    ids: FxHashMap<ConstraintNode<'tcx>, NodeId>,
+}
+
+impl<'tcx> NodeInterner<'tcx> {
+    fn intern(&mut self, node: ConstraintNode<'tcx>) -> NodeId {
+        if let Some(&id) = self.ids.get(&node) {
+            id
+        } else {
+            let id = self.nodes.push(node);
+            self.ids.insert(node, id);
+            id
+        }
+    }
+
+    fn get(&self, id: NodeId) -> ConstraintNode<'tcx> {
+        self.nodes[id]
+    }
+
+    fn domain_size(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+type InternedPointsTo = FxHashMap<NodeId, HybridBitSet<NodeId>>;
+
 impl<'a, 'tcx> Andersen<'a, 'tcx> {
     pub fn new(body: &'a Body<'tcx>, tcx: TyCtxt<'tcx>) -> Self {
         Self {
             body,
             tcx,
+            interner: Default::default(),
+            pts: Default::default(),
+            cache: None,
+            cached_result: None,
+        }
+    }
+
+    /// Same as `new`, but `analyze` first checks `cache` for a previous
+    /// run's `PointsToMap` keyed by `def_path`'s current body fingerprint,
+    /// and loads it instead of re-solving when the fingerprint matches.
+    /// `def_path` should be stable across compilations of the same source
+    /// -- `tcx.def_path_str(def_id)`, the same key `crate::cache` uses to
+    /// fingerprint instances.
+    pub fn new_with_cache(body: &'a Body<'tcx>, tcx: TyCtxt<'tcx>, cache: &'a cache::PointsToCache, def_path: String) -> Self {
+        Self {
+            body,
+            tcx,
+            interner: Default::default(),
             pts: Default::default(),
+            cache: Some((cache, def_path)),
+            cached_result: None,
         }
     }
 
     pub fn analyze(&mut self) {
+        let fingerprint = self.cache.is_some().then(|| cache::body_fingerprint(self.body));
+        if let (Some((disk_cache, def_path)), Some(fingerprint)) = (&self.cache, &fingerprint) {
+            if let Some(cached) = disk_cache.load(self.tcx, self.body, def_path, fingerprint) {
+                self.cached_result = Some(cached);
+                return;
+            }
+        }
+
         let mut collector = ConstraintGraphCollector::new(self.body, self.tcx);
         collector.visit_body(self.body);
         let mut graph = collector.finish();
-        let mut worklist = VecDeque::new();
+        let mut pending_worklist_nodes = Vec::new();
         // 首先模拟编译器分配内存地址
         // alloc: place = alloc
         for node in graph.nodes() {
@@ -73,77 +168,147 @@ impl<'a, 'tcx> Andersen<'a, 'tcx> {
                 ConstraintNode::Constant(constant) => {
                     graph.add_constant(constant);
                     // For constant C, track *C.
-                    worklist.push_back(ConstraintNode::ConstantDeref(constant));
+                    pending_worklist_nodes.push(ConstraintNode::ConstantDeref(constant));
                 }
                 _ => {}
             }
-            worklist.push_back(node);
+            pending_worklist_nodes.push(node);
         }
+
+        // The graph's node set is final now: everything from here on
+        // (`collapse_copy_cycles` included) only merges existing nodes
+        // together, it never mints a new `ConstraintNode` value. So intern
+        // the whole node set up front and size every bitset to it.
+        for node in graph.nodes() {
+            self.interner.intern(node);
+        }
+        let domain_size = self.interner.domain_size();
+        let mut worklist: VecDeque<NodeId> = pending_worklist_nodes
+            .into_iter()
+            .map(|node| self.interner.intern(node))
+            .collect();
+
         // address: target = &source
         for (source, target, weight) in graph.edges() {
             if weight == ConstraintEdge::Address {
-                self.pts.entry(target).or_default().insert(source);
-                
+                let source = self.interner.intern(source);
+                let target = self.interner.intern(target);
+                self.pts
+                    .entry(target)
+                    .or_insert_with(|| HybridBitSet::new_empty(domain_size))
+                    .insert(source);
+
                 worklist.push_back(target);
             }
         }
 
-        while let Some(node) = worklist.pop_front() {
+        // How many worklist pops between SCC collapses -- chosen over
+        // collapsing the instant `insert_edge` closes a Copy cycle so the
+        // (more expensive) `tarjan_scc` pass amortizes over a batch of
+        // cheap worklist steps instead of running on every single Copy
+        // edge insertion.
+        const COLLAPSE_INTERVAL: usize = 64;
+        let mut since_collapse = 0usize;
+
+        while let Some(node_id) = worklist.pop_front() {
+            since_collapse += 1;
+            if since_collapse >= COLLAPSE_INTERVAL {
+                graph.collapse_copy_cycles(&mut self.pts, &mut self.interner);
+                since_collapse = 0;
+            }
+            // `node` may have been collapsed into a representative since it
+            // was pushed; resolve it so the rest of this iteration (and the
+            // `self.pts` lookups below) operate on the live node.
+            let node = self.interner.intern(graph.resolve(self.interner.get(node_id)));
             if !self.pts.contains_key(&node) {
                 continue;
             }
-            for o in self.pts.get(&node).unwrap() {
+            let pointees: Vec<NodeId> = self.pts.get(&node).unwrap().iter().collect();
+            for o in pointees {
                 // store: *node = source
-                for source in graph.store_sources(&node) {
-                    if graph.insert_edge(source, *o, ConstraintEdge::Copy) {
-                        worklist.push_back(source);
+                for source in graph.store_sources(&self.interner.get(node)) {
+                    if graph.insert_edge(source, self.interner.get(o), ConstraintEdge::Copy) {
+                        worklist.push_back(self.interner.intern(source));
                     }
                 }
                 // load: target = *node
-                for target in graph.load_targets(&node) {
-                    if graph.insert_edge(*o, target, ConstraintEdge::Copy) {
-                        worklist.push_back(*o);
+                for target in graph.load_targets(&self.interner.get(node)) {
+                    if graph.insert_edge(self.interner.get(o), target, ConstraintEdge::Copy) {
+                        worklist.push_back(o);
                     }
                 }
             }
             // alias_copy: target = &X; X = ptr::read(node)
-            for target in graph.alias_copy_targets(&node) {
-                if graph.insert_edge(node, target, ConstraintEdge::Copy) {
+            for target in graph.alias_copy_targets(&self.interner.get(node)) {
+                if graph.insert_edge(self.interner.get(node), target, ConstraintEdge::Copy) {
                     worklist.push_back(node);
                 }
             }
             // copy: target = node
-            for target in graph.copy_targets(&node) {
-                if self.union_pts(&target, &node) {
+            for target in graph.copy_targets(&self.interner.get(node)) {
+                let target = self.interner.intern(target);
+                if self.union_pts(target, node) {
                     worklist.push_back(target);
                 }
             }
 
         }
+
+        // `collapse_copy_cycles` only ever keys `self.pts` by the
+        // representative of a merged Copy-cycle, so every node that got
+        // merged away is otherwise missing from the final map. Backfill
+        // those original nodes here so a caller querying `finish()`'s
+        // result with any pre-collapse `ConstraintNode` still finds its
+        // (shared) points-to set, same as if collapsing had never run.
+        for member in graph.merged_nodes() {
+            let rep = graph.resolve(member);
+            let member = self.interner.intern(member);
+            let rep = self.interner.intern(rep);
+            if let Some(rep_pts) = self.pts.get(&rep).cloned() {
+                self.pts.insert(member, rep_pts);
+            }
+        }
+
+        if let (Some((disk_cache, def_path)), Some(fingerprint)) = (&self.cache, &fingerprint) {
+            disk_cache.store(def_path, fingerprint, &self.decode());
+        }
     }
 
     /// pts(target) = pts(target) U pts(source), return true if pts(target) changed
-    fn union_pts(&mut self, target: &ConstraintNode<'tcx>, source: &ConstraintNode<'tcx>) -> bool {
+    fn union_pts(&mut self, target: NodeId, source: NodeId) -> bool {
         // skip Alloc target
-        if matches!(target, ConstraintNode::Alloc(_)) {
+        if matches!(self.interner.get(target), ConstraintNode::Alloc(_)) {
             return false;
         }
-        if self.pts.get(target).is_none() {
-            let source_pts = self.pts.get(source).unwrap().clone();      
-            self.pts.insert(*target, source_pts);
-            return true;
-        } else {
-            let old_len = self.pts.get(target).unwrap().len();
-            let source_pts = self.pts.get(source).unwrap().clone();      
-            let target_pts = self.pts.get_mut(target).unwrap();
-            target_pts.extend(source_pts.into_iter());
-            old_len != target_pts.len()
+        let domain_size = self.interner.domain_size();
+        let source_pts = self.pts.get(&source).unwrap().clone();
+        let changed_on_insert = !self.pts.contains_key(&target);
+        let target_pts = self
+            .pts
+            .entry(target)
+            .or_insert_with(|| HybridBitSet::new_empty(domain_size));
+        let changed = target_pts.union(&source_pts);
+        changed_on_insert || changed
+    }
+
+    /// Decode the interned, bitset-backed working state back into the
+    /// `ConstraintNode`-keyed map every other caller (`AliasAnalysis`,
+    /// detectors) already expects -- interning is purely an
+    /// implementation detail of the solver's inner loop.
+    fn decode(&self) -> PointsToMap<'tcx> {
+        let mut result: PointsToMap<'tcx> = FxHashMap::default();
+        for (node, pointees) in &self.pts {
+            let entry = result.entry(self.interner.get(*node)).or_default();
+            entry.extend(pointees.iter().map(|pointee| self.interner.get(pointee)));
         }
-        
+        result
     }
 
-    pub fn finish(self) -> FxHashMap<ConstraintNode<'tcx>, FxHashSet<ConstraintNode<'tcx>>> {
-        self.pts
+    pub fn finish(self) -> PointsToMap<'tcx> {
+        match self.cached_result {
+            Some(cached) => cached,
+            None => self.decode(),
+        }
     }
 }
 
@@ -225,10 +390,28 @@ enum AccessPattern<'tcx> {
 struct ConstraintGraph<'tcx> {
     graph: Graph<ConstraintNode<'tcx>, ConstraintEdge, Directed>,
     node_map: FxHashMap<ConstraintNode<'tcx>, NodeIndex>,
+    /// Union-find parent pointers written by `collapse_copy_cycles`: a
+    /// collapsed node maps to the representative it was merged into. Nodes
+    /// never collapsed simply have no entry here.
+    uf: FxHashMap<ConstraintNode<'tcx>, ConstraintNode<'tcx>>,
 }
 
 impl<'tcx> ConstraintGraph<'tcx> {
+    /// Resolve `node` to its current representative, following the
+    /// union-find chain `collapse_copy_cycles` may have built. A no-op for
+    /// any node that was never merged into something else.
+    fn resolve(&self, mut node: ConstraintNode<'tcx>) -> ConstraintNode<'tcx> {
+        while let Some(&rep) = self.uf.get(&node) {
+            if rep == node {
+                break;
+            }
+            node = rep;
+        }
+        node
+    }
+
     fn get_or_insert_node(&mut self, node: ConstraintNode<'tcx>) -> NodeIndex {
+        let node = self.resolve(node);
         if let Some(idx) = self.node_map.get(&node) {
             *idx
         } else {
@@ -239,7 +422,7 @@ impl<'tcx> ConstraintGraph<'tcx> {
     }
 
     fn get_node(&self, node: &ConstraintNode<'tcx>) -> Option<NodeIndex> {
-        self.node_map.get(node).copied()
+        self.node_map.get(&self.resolve(*node)).copied()
     }
 
     fn add_alloc(&mut self, place: PlaceRef<'tcx>) {
@@ -382,7 +565,7 @@ impl<'tcx> ConstraintGraph<'tcx> {
         let mut sources = Vec::new();
         for edge in self.graph.edges_directed(lhs1, Direction::Incoming) {
             if *edge.weight() == ConstraintEdge::Store {
-                let source = self.graph.node_weight(edge.source()).copied().unwrap();
+                let source = self.resolve(self.graph.node_weight(edge.source()).copied().unwrap());
                 sources.push(source);
             }
         }
@@ -396,7 +579,7 @@ impl<'tcx> ConstraintGraph<'tcx> {
         let mut targets = Vec::new();
         for edge in self.graph.edges_directed(rhs, Direction::Outgoing) {
             if *edge.weight() == ConstraintEdge::Load {
-                let target = self.graph.node_weight(edge.target()).copied().unwrap();
+                let target = self.resolve(self.graph.node_weight(edge.target()).copied().unwrap());
                 targets.push(target);
             }
         }
@@ -410,13 +593,13 @@ impl<'tcx> ConstraintGraph<'tcx> {
         let mut targets = Vec::new();
         for edge in self.graph.edges_directed(rhs, Direction::Outgoing) {
             if *edge.weight() == ConstraintEdge::Copy {
-                let target = self.graph.node_weight(edge.target()).copied().unwrap();
+                let target = self.resolve(self.graph.node_weight(edge.target()).copied().unwrap());
                 match target{
                     ConstraintNode::Alloc(_) => {
                         let rhs_target = self.get_node(&target).unwrap();
                         for edge1 in self.graph.edges_directed(rhs_target, Direction::Outgoing) {
                             if *edge1.weight() == ConstraintEdge::Address {
-                                let target1 = self.graph.node_weight(edge1.target()).copied().unwrap();
+                                let target1 = self.resolve(self.graph.node_weight(edge1.target()).copied().unwrap());
                                 targets.push(target1);
                                 break;
                             }
@@ -453,7 +636,7 @@ impl<'tcx> ConstraintGraph<'tcx> {
                     .edges_directed(copy_alias_target, Direction::Outgoing)
                     .filter_map(|edge| {
                         if *edge.weight() == ConstraintEdge::Address {
-                            Some(self.graph.node_weight(edge.target()).copied().unwrap())
+                            Some(self.resolve(self.graph.node_weight(edge.target()).copied().unwrap()))
                         } else {
                             None
                         }
@@ -484,6 +667,88 @@ impl<'tcx> ConstraintGraph<'tcx> {
         true
     }
 
+    /// Collapse every strongly-connected component of the Copy-only
+    /// subgraph into a single representative node. A Copy cycle (`a = b; b
+    /// = a`) means `pts(a)` and `pts(b)` are forced equal forever after, so
+    /// the worklist re-unioning both of them to a fixpoint on every visit
+    /// is wasted work once the cycle is known; collapsing it into one node
+    /// makes that equality structural instead of re-derived.
+    ///
+    /// Only `ConstraintEdge::Copy` edges are considered for SCC detection:
+    /// Load/Store/Address encode a dereference or allocation step rather
+    /// than equality, so a cycle that runs through one of those isn't safe
+    /// to collapse (it would conflate two distinct memory cells).
+    ///
+    /// Collapsed nodes are dropped from `node_map` (so `nodes()`/`get_node`
+    /// stop seeing them as distinct) and recorded in `uf`, but are left in
+    /// place in the underlying `petgraph` graph rather than physically
+    /// removed -- `Graph::remove_node` is a swap-remove that would
+    /// invalidate other live `NodeIndex`es mid-collapse. The stale edges
+    /// this leaves behind are harmless: every accessor above resolves node
+    /// weights through `uf` before using them.
+    fn collapse_copy_cycles(&mut self, pts: &mut InternedPointsTo, interner: &mut NodeInterner<'tcx>) {
+        let copy_only = EdgeFiltered::from_fn(&self.graph, |edge| *edge.weight() == ConstraintEdge::Copy);
+        let sccs = tarjan_scc(&copy_only);
+        for scc in sccs {
+            if scc.len() < 2 {
+                continue;
+            }
+            let rep_idx = *scc.iter().min().unwrap();
+            let rep = self.resolve(self.graph.node_weight(rep_idx).copied().unwrap());
+            let rep_id = interner.intern(rep);
+            for &member_idx in &scc {
+                if member_idx == rep_idx {
+                    continue;
+                }
+                let member = self.graph.node_weight(member_idx).copied().unwrap();
+                if member == rep {
+                    continue;
+                }
+                self.uf.insert(member, rep);
+                self.node_map.remove(&member);
+
+                let member_id = interner.intern(member);
+                if let Some(member_pts) = pts.remove(&member_id) {
+                    let domain_size = interner.domain_size();
+                    pts.entry(rep_id)
+                        .or_insert_with(|| HybridBitSet::new_empty(domain_size))
+                        .union(&member_pts);
+                }
+
+                let incident: Vec<(NodeIndex, NodeIndex, ConstraintEdge)> = self
+                    .graph
+                    .edges_directed(member_idx, Direction::Incoming)
+                    .map(|e| (e.source(), member_idx, *e.weight()))
+                    .chain(
+                        self.graph
+                            .edges_directed(member_idx, Direction::Outgoing)
+                            .map(|e| (member_idx, e.target(), *e.weight())),
+                    )
+                    .collect();
+                for (source, target, weight) in incident {
+                    let new_source = if source == member_idx { rep_idx } else { source };
+                    let new_target = if target == member_idx { rep_idx } else { target };
+                    if new_source == new_target {
+                        // Self-edge introduced by the merge: `a == a` under
+                        // the new equality, nothing further to redirect.
+                        continue;
+                    }
+                    if self.graph.find_edge(new_source, new_target).is_none() {
+                        self.graph.add_edge(new_source, new_target, weight);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every node ever merged away by `collapse_copy_cycles`, i.e. every key
+    /// of `uf`. Used by `Andersen::analyze` to backfill `self.pts` for
+    /// original nodes once collapsing has started keying it by
+    /// representative only.
+    fn merged_nodes(&self) -> Vec<ConstraintNode<'tcx>> {
+        self.uf.keys().copied().collect()
+    }
+
     /// Print the callgraph in dot format.
     #[allow(dead_code)]
     pub fn dot(&self) {
@@ -799,9 +1064,19 @@ pub struct AliasId {
 /// It answers if two memory cells alias with each other.
 /// It performs an underlying points-to analysis if needed.
 /// The points-to info will be cached into `pts` for future queries.
+///
+/// `pts` is `RwLock`-guarded rather than a plain `&mut`-accessed map so a
+/// single `AliasAnalysis` can be shared (behind an `Arc`) across a rayon
+/// thread pool analyzing independent `DefId`s concurrently: readers don't
+/// block each other, and a cache hit never re-runs Andersen's analysis.
 pub struct AliasAnalysis<'tcx> {
     tcx: TyCtxt<'tcx>,
-    pts: FxHashMap<DefId, PointsToMap<'tcx>>,
+    pts: RwLock<FxHashMap<DefId, PointsToMap<'tcx>>>,
+    /// When set, every `get_or_insert_pts` miss is first checked against
+    /// (and then written back to) this cross-session disk cache before
+    /// falling back to a full `Andersen::analyze` solve. See
+    /// `cache::PointsToCache`.
+    disk_cache: Option<cache::PointsToCache>,
 }
 
 impl<'tcx> AliasAnalysis<'tcx> {
@@ -809,11 +1084,25 @@ impl<'tcx> AliasAnalysis<'tcx> {
         Self {
             tcx,
             pts: Default::default(),
+            disk_cache: None,
+        }
+    }
+
+    /// Same as `new`, but every points-to solve this analysis would
+    /// otherwise redo from scratch on the next `rustc` invocation is first
+    /// checked against a disk cache under `output_directory` -- unlike
+    /// `pts` above (which only avoids repeat solves within this one
+    /// process), this survives across separate compiler runs.
+    pub fn new_with_cache_dir(tcx: TyCtxt<'tcx>, output_directory: &std::path::Path) -> Self {
+        Self {
+            tcx,
+            pts: Default::default(),
+            disk_cache: Some(cache::PointsToCache::new(output_directory)),
         }
     }
 
 
-    pub fn load_corrlation(&mut self, body: &Body<'tcx>, load_interimval: &HashSet<PlaceRef<'tcx>>) -> bool {
+    pub fn load_corrlation(&self, body: &Body<'tcx>, load_interimval: &HashSet<PlaceRef<'tcx>>) -> bool {
         let mut collector = ConstraintGraphCollector::new(body, self.tcx);
         collector.visit_body(body);
         let graph = collector.finish();
@@ -835,16 +1124,24 @@ impl<'tcx> AliasAnalysis<'tcx> {
     /// Get the points-to info from cache `pts`.
     /// If not exists, then perform points-to analysis
     /// and add the obtained points-to info to cache.
-    pub fn get_or_insert_pts(&mut self, def_id: DefId, body: &Body<'tcx>) -> PointsToMap<'tcx> {
-        if self.pts.contains_key(&def_id) {
-            self.pts.get(&def_id).unwrap().clone()
-        } else {
-            let mut pointer_analysis = Andersen::new(body, self.tcx);
-            
-            pointer_analysis.analyze();
-            let pts = pointer_analysis.finish();
-            self.pts.entry(def_id).or_insert(pts.clone()).clone()
+    ///
+    /// Takes `&self` so this can be called through a shared `Arc<AliasAnalysis>`
+    /// from multiple worker threads at once. Two threads racing on the same
+    /// `def_id` may both miss the cache and redo the Andersen analysis; that's
+    /// wasted work, not a correctness problem, since both will write the same
+    /// result back.
+    pub fn get_or_insert_pts(&self, def_id: DefId, body: &Body<'tcx>) -> PointsToMap<'tcx> {
+        if let Some(pts) = self.pts.read().unwrap().get(&def_id) {
+            return pts.clone();
         }
+        let mut pointer_analysis = match &self.disk_cache {
+            Some(disk_cache) => Andersen::new_with_cache(body, self.tcx, disk_cache, self.tcx.def_path_str(def_id)),
+            None => Andersen::new(body, self.tcx),
+        };
+
+        pointer_analysis.analyze();
+        let pts = pointer_analysis.finish();
+        self.pts.write().unwrap().entry(def_id).or_insert(pts.clone()).clone()
     }
 
     // pub fn is_atomic_in_adt(
@@ -0,0 +1,66 @@
+//! `atomvchecker-json <rust-project.json>` drives the same analysis as
+//! `cargo atomvchecker`, but off a `rust-project.json` project model
+//! instead of a Cargo-built crate graph -- for Buck/Bazel/kernel-module
+//! style builds that never go through `cargo build`. Crates are compiled
+//! through the `atomvchecker` rustc wrapper in dependency order, so every
+//! `--extern` path is available by the time a dependent crate needs it.
+//! Flags after `--` are forwarded to `atomvchecker` the same way
+//! `cargo atomvchecker` forwards them via `ATOMVCHECKER_FLAGS`.
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+use atomvchecker::project::{self, JsonProject};
+
+const HELP: &str = r#"Statically detect bugs on MIR for a rust-project.json-described build
+Usage:
+    atomvchecker-json <rust-project.json> [-- <atomvchecker flags>]
+"#;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let Some(project_path) = args.next() else {
+        println!("{}", HELP);
+        std::process::exit(1);
+    };
+    if project_path == "--help" || project_path == "-h" {
+        println!("{}", HELP);
+        return;
+    }
+    let flags: Vec<String> = args.skip_while(|arg| arg != "--").skip(1).collect();
+
+    let Some(project) = JsonProject::load(PathBuf::from(&project_path).as_path()) else {
+        eprintln!("could not read or parse {}", project_path);
+        std::process::exit(1);
+    };
+
+    let out_dir = env::temp_dir().join("atomvchecker-json-out");
+    if std::fs::create_dir_all(&out_dir).is_err() {
+        eprintln!("could not create scratch output dir {}", out_dir.display());
+        std::process::exit(1);
+    }
+
+    let mut rlib_for: HashMap<usize, PathBuf> = HashMap::new();
+    for idx in project.build_order() {
+        let krate = &project.crates[idx];
+        let rustc_args = project::rustc_args(&project, krate, &rlib_for, &out_dir);
+
+        let mut cmd = Command::new("atomvchecker");
+        cmd.args(&rustc_args);
+        cmd.env("RUST_BACKTRACE", "full");
+        cmd.env("ATOMVCHECKER_LOG", "info");
+        cmd.env("ATOMVCHECKER_FLAGS", flags.join(" "));
+
+        let status = cmd
+            .spawn()
+            .expect("could not run atomvchecker")
+            .wait()
+            .expect("failed to wait for atomvchecker?");
+        if !status.success() {
+            eprintln!("atomvchecker failed for crate {}, skipping its dependents' --extern", krate.display_name);
+            continue;
+        }
+        rlib_for.insert(idx, out_dir.join(format!("lib{}.rlib", krate.display_name)));
+    }
+}
@@ -15,7 +15,11 @@ Common options:
     -k, --detector-kind      Choose detector, deadlock
     -b, --blacklist-mode     Use crate-name-list as blacklist, whitelist if not specified
     -l, --crate-name-list    Will not white-or-black list the crates if not specified.
-    
+                             Entries may be a crate name or a `cfg(...)` predicate, e.g.
+                             `cfg(all(target_os = "linux", not(feature = "no_async")))`.
+    --fix                    Apply machine-applicable fix suggestions in place instead of printing them.
+    --message-format=json    Emit findings as rustc-style JSON diagnostics for editor flycheck integration.
+
 Other [options] are the same as `cargo build`. Everything after the second "--" verbatim
 to the program.
 Examples:
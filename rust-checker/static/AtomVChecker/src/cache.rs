@@ -0,0 +1,195 @@
+//! Per-crate result cache so `cargo atomvchecker` can be re-run with
+//! different `-k`/`-l` flags without a `cargo clean` in between.
+//! Findings for a crate are keyed by a Merkle-style fingerprint of the
+//! crate name, the detector kind, and every reachable instance's own
+//! fingerprint; a cache hit replays the stored findings instead of
+//! re-running the detectors.
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use petgraph::visit::IntoNodeReferences;
+use petgraph::Direction::Outgoing;
+use rustc_hash::FxHasher;
+use rustc_middle::ty::TyCtxt;
+
+use crate::analysis::callgraph::{CallGraph, InstanceId};
+use crate::detector::atomic::fix::FixSet;
+use crate::detector::report::Report;
+use crate::options::DetectorKind;
+
+/// Stable fingerprint of a crate's analysis inputs, built bottom-up over
+/// the callgraph the way rustc's own dep-graph fingerprints a query: every
+/// instance's own fingerprint folds in the fingerprints of its direct
+/// callees, so a change anywhere in an instance's transitive call tree
+/// changes its fingerprint (and so every caller's, all the way up),
+/// without re-hashing the MIR of every unrelated function in the crate.
+///
+/// Detection itself still re-runs over the whole crate on any change --
+/// the detectors aren't decomposed to run per-instance -- but persisting
+/// `instance_fingerprints` here, keyed the same way a future per-instance
+/// report cache would be, means that decomposition can reuse this instead
+/// of re-deriving which functions actually changed.
+pub fn fingerprint<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    crate_name: &str,
+    detector_kind: DetectorKind,
+    callgraph: &CallGraph<'tcx>,
+    cache: &ResultCache,
+) -> String {
+    let per_instance = instance_fingerprints(tcx, callgraph);
+    cache.store_instance_fingerprints(&per_instance);
+
+    let mut hasher = FxHasher::default();
+    crate_name.hash(&mut hasher);
+    format!("{:?}", detector_kind).hash(&mut hasher);
+    let mut entries: Vec<(&String, &u64)> = per_instance.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    for (def_path, instance_fp) in entries {
+        def_path.hash(&mut hasher);
+        instance_fp.hash(&mut hasher);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// A fixed stand-in fingerprint for a callee still being visited higher up
+/// the recursion (i.e. we're inside a recursive or mutually-recursive
+/// cycle). Sound -- changing any member's own body still changes that
+/// member's own-body hash, which every other member in the cycle folds
+/// in transitively on the *next* run once the cycle is no longer
+/// in-progress -- but coarser than necessary: within a single run, every
+/// member of a cycle gets the same placeholder for its recursive callees
+/// rather than each other's real fingerprints.
+const CYCLE_PLACEHOLDER: u64 = 0x5EC7_0000_5EC7_0000;
+
+/// Every instance reachable in `callgraph`, fingerprinted by def-path
+/// string (stable across compilations of the same source, unlike
+/// `InstanceId`, which is just a graph index).
+fn instance_fingerprints<'tcx>(tcx: TyCtxt<'tcx>, callgraph: &CallGraph<'tcx>) -> HashMap<String, u64> {
+    let mut memo: HashMap<InstanceId, u64> = HashMap::new();
+    let mut in_progress: HashSet<InstanceId> = HashSet::new();
+    let ids: Vec<InstanceId> = callgraph.graph.node_references().map(|(id, _)| id).collect();
+    for &id in &ids {
+        instance_fingerprint(tcx, callgraph, id, &mut memo, &mut in_progress);
+    }
+    ids.into_iter()
+        .map(|id| {
+            let inst = callgraph.index_to_instance(id).unwrap();
+            (tcx.def_path_str(inst.instance().def_id()), memo[&id])
+        })
+        .collect()
+}
+
+fn instance_fingerprint<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    callgraph: &CallGraph<'tcx>,
+    id: InstanceId,
+    memo: &mut HashMap<InstanceId, u64>,
+    in_progress: &mut HashSet<InstanceId>,
+) -> u64 {
+    if let Some(&fp) = memo.get(&id) {
+        return fp;
+    }
+    if !in_progress.insert(id) {
+        return CYCLE_PLACEHOLDER;
+    }
+
+    let inst = callgraph.index_to_instance(id).unwrap();
+    let def_id = inst.instance().def_id();
+    let mut hasher = FxHasher::default();
+    tcx.def_path_str(def_id).hash(&mut hasher);
+    if tcx.is_mir_available(def_id) {
+        let body = tcx.instance_mir(inst.instance().def);
+        format!("{:#?}", body).hash(&mut hasher);
+    }
+
+    let mut callee_fps: Vec<u64> = callgraph
+        .graph
+        .neighbors_directed(id, Outgoing)
+        .map(|callee| instance_fingerprint(tcx, callgraph, callee, memo, in_progress))
+        .collect();
+    callee_fps.sort_unstable();
+    for callee_fp in callee_fps {
+        callee_fp.hash(&mut hasher);
+    }
+
+    let fp = hasher.finish();
+    in_progress.remove(&id);
+    memo.insert(id, fp);
+    fp
+}
+
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    pub fn new(output_directory: &Path) -> Self {
+        Self {
+            dir: output_directory.join("atomvchecker-cache"),
+        }
+    }
+
+    fn entry_path(&self, fingerprint: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", fingerprint))
+    }
+
+    fn instance_fingerprints_path(&self) -> PathBuf {
+        self.dir.join("instance-fingerprints.json")
+    }
+
+    /// Per-instance fingerprints recorded on a previous run, keyed by
+    /// def-path string. Empty (not an error) the first time this crate is
+    /// ever analyzed.
+    pub fn load_instance_fingerprints(&self) -> HashMap<String, u64> {
+        fs::read_to_string(self.instance_fingerprints_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn store_instance_fingerprints(&self, fingerprints: &HashMap<String, u64>) {
+        if fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        if let Ok(content) = serde_json::to_string(fingerprints) {
+            let _ = fs::write(self.instance_fingerprints_path(), content);
+        }
+    }
+
+    pub fn load(&self, fingerprint: &str) -> Option<Vec<Report>> {
+        self.load_entry(fingerprint)
+    }
+
+    pub fn store(&self, fingerprint: &str, reports: &[Report]) {
+        self.store_entry(fingerprint, reports)
+    }
+
+    /// Like `load`, but for a detector (e.g. `AtomicityViolationDetector`)
+    /// whose cache entry also needs to replay the `FixSet` suggestions
+    /// that came with the findings on a cold run -- without this, a cache
+    /// hit would silently drop `--fix`'s suggestions instead of replaying
+    /// them, since they'd never have been persisted.
+    pub fn load_with_fixes(&self, fingerprint: &str) -> Option<(Vec<Report>, FixSet)> {
+        self.load_entry(fingerprint)
+    }
+
+    pub fn store_with_fixes(&self, fingerprint: &str, reports: &[Report], fixes: &FixSet) {
+        self.store_entry(fingerprint, &(reports, fixes))
+    }
+
+    fn load_entry<T: serde::de::DeserializeOwned>(&self, fingerprint: &str) -> Option<T> {
+        let content = fs::read_to_string(self.entry_path(fingerprint)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn store_entry<T: serde::Serialize>(&self, fingerprint: &str, value: &T) {
+        if fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        if let Ok(content) = serde_json::to_string(value) {
+            let _ = fs::write(self.entry_path(fingerprint), content);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! `cfg(...)` predicates for crate-name-list entries.
+//! `-l/--crate-name-list` entries are plain crate names by default, but an
+//! entry may instead be a `cfg(...)` expression (`all()`, `any()`, `not()`,
+//! bare identifiers, and `key = "value"` pairs) evaluated against the
+//! compilation's cfg set, e.g.
+//! `-l 'cfg(all(target_os = "linux", not(feature = "no_async")))'` to
+//! restrict detection to platform-specific concurrency code without
+//! enumerating crate names by hand.
+use rustc_middle::ty::TyCtxt;
+use rustc_span::symbol::Symbol;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfgExpr {
+    All(Vec<CfgExpr>),
+    Any(Vec<CfgExpr>),
+    Not(Box<CfgExpr>),
+    Option(String, Option<String>),
+}
+
+impl CfgExpr {
+    /// Parse a `cfg(...)` predicate. Returns `None` if `input` isn't a
+    /// `cfg(...)` expression at all (a plain crate name).
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let inner = input.strip_prefix("cfg(")?.strip_suffix(')')?;
+        let mut parser = Parser { rest: inner };
+        let expr = parser.parse_expr()?;
+        parser.skip_ws();
+        if parser.rest.is_empty() {
+            Some(expr)
+        } else {
+            None
+        }
+    }
+
+    pub fn eval(&self, cfg_set: &[(Symbol, Option<Symbol>)]) -> bool {
+        match self {
+            CfgExpr::All(exprs) => exprs.iter().all(|e| e.eval(cfg_set)),
+            CfgExpr::Any(exprs) => exprs.iter().any(|e| e.eval(cfg_set)),
+            CfgExpr::Not(expr) => !expr.eval(cfg_set),
+            CfgExpr::Option(key, value) => cfg_set.iter().any(|(k, v)| {
+                k.as_str() == key && value.as_deref() == v.map(|s| s.as_str())
+            }),
+        }
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_ws(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, tok: &str) -> bool {
+        self.skip_ws();
+        match self.rest.strip_prefix(tok) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn parse_ident(&mut self) -> Option<&'a str> {
+        self.skip_ws();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return None;
+        }
+        let (ident, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(ident)
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        self.skip_ws();
+        let rest = self.rest.strip_prefix('"')?;
+        let end = rest.find('"')?;
+        let (value, rest) = rest.split_at(end);
+        self.rest = &rest[1..];
+        Some(value.to_owned())
+    }
+
+    fn parse_list(&mut self) -> Option<Vec<CfgExpr>> {
+        if !self.eat("(") {
+            return None;
+        }
+        let mut exprs = Vec::new();
+        loop {
+            self.skip_ws();
+            if self.eat(")") {
+                break;
+            }
+            exprs.push(self.parse_expr()?);
+            self.skip_ws();
+            if self.eat(",") {
+                continue;
+            }
+            if self.eat(")") {
+                break;
+            }
+            return None;
+        }
+        Some(exprs)
+    }
+
+    fn parse_expr(&mut self) -> Option<CfgExpr> {
+        let ident = self.parse_ident()?;
+        match ident {
+            "all" => Some(CfgExpr::All(self.parse_list()?)),
+            "any" => Some(CfgExpr::Any(self.parse_list()?)),
+            "not" => {
+                let mut exprs = self.parse_list()?;
+                if exprs.len() != 1 {
+                    return None;
+                }
+                Some(CfgExpr::Not(Box::new(exprs.pop().unwrap())))
+            }
+            key => {
+                self.skip_ws();
+                if self.eat("=") {
+                    Some(CfgExpr::Option(key.to_owned(), Some(self.parse_string()?)))
+                } else {
+                    Some(CfgExpr::Option(key.to_owned(), None))
+                }
+            }
+        }
+    }
+}
+
+/// Whether a crate-name-list `entry` selects `crate_name`: a `cfg(...)`
+/// entry is evaluated against the compilation's cfg set, anything else
+/// falls back to plain crate-name equality.
+pub fn entry_matches(entry: &str, crate_name: &str, tcx: TyCtxt<'_>) -> bool {
+    match CfgExpr::parse(entry) {
+        Some(expr) => {
+            let cfg_set: Vec<(Symbol, Option<Symbol>)> =
+                tcx.sess.parse_sess.config.iter().copied().collect();
+            expr.eval(&cfg_set)
+        }
+        None => entry == crate_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CfgExpr;
+
+    #[test]
+    fn bare_ident_parses() {
+        assert_eq!(CfgExpr::parse("cfg(feature)"), Some(CfgExpr::Option("feature".to_owned(), None)));
+    }
+
+    #[test]
+    fn key_value_parses() {
+        assert_eq!(
+            CfgExpr::parse(r#"cfg(feature = "x")"#),
+            Some(CfgExpr::Option("feature".to_owned(), Some("x".to_owned()))),
+        );
+    }
+
+    /// `parse_list`'s loop used to keep trying to parse another element
+    /// after consuming the closing `)`, so every compound form (`all`,
+    /// `any`, `not`, and anything nesting them) parsed to `None`.
+    #[test]
+    fn not_parses() {
+        assert_eq!(
+            CfgExpr::parse(r#"cfg(not(feature = "x"))"#),
+            Some(CfgExpr::Not(Box::new(CfgExpr::Option("feature".to_owned(), Some("x".to_owned()))))),
+        );
+    }
+
+    #[test]
+    fn any_parses() {
+        assert_eq!(
+            CfgExpr::parse("cfg(any(a, b))"),
+            Some(CfgExpr::Any(vec![
+                CfgExpr::Option("a".to_owned(), None),
+                CfgExpr::Option("b".to_owned(), None),
+            ])),
+        );
+    }
+
+    #[test]
+    fn nested_all_parses() {
+        assert_eq!(
+            CfgExpr::parse(r#"cfg(all(target_os = "linux", not(feature = "no_async")))"#),
+            Some(CfgExpr::All(vec![
+                CfgExpr::Option("target_os".to_owned(), Some("linux".to_owned())),
+                CfgExpr::Not(Box::new(CfgExpr::Option("feature".to_owned(), Some("no_async".to_owned())))),
+            ])),
+        );
+    }
+}
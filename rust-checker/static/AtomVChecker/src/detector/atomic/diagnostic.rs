@@ -0,0 +1,93 @@
+//! Render detector findings as rustc-style JSON diagnostics so editor
+//! flycheck integrations (cargo-watch, rust-analyzer's `checkOnSave`) can
+//! parse our output the same way they already parse `cargo check
+//! --message-format=json`.
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::detector::report::Report;
+
+/// A single span inside a diagnostic, mirroring the shape rustc emits for
+/// `--error-format=json` (only the fields flycheck tooling actually reads).
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticSpan {
+    pub file_name: String,
+    pub byte_start: u32,
+    pub byte_end: u32,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub column_start: u32,
+    pub column_end: u32,
+    pub is_primary: bool,
+    pub suggested_replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub level: String,
+    pub spans: Vec<DiagnosticSpan>,
+    pub children: Vec<Diagnostic>,
+}
+
+impl DiagnosticSpan {
+    fn parse(source_info: &str, is_primary: bool, suggested_replacement: Option<String>) -> Option<Self> {
+        // `span_to_diagnostic_string` renders e.g. "src/main.rs:10:5: 12:20 (#0)".
+        let re = Regex::new(r"^(?P<file>.+):(?P<ls>\d+):(?P<cs>\d+): (?P<le>\d+):(?P<ce>\d+)").unwrap();
+        let caps = re.captures(source_info)?;
+        Some(Self {
+            file_name: caps["file"].to_owned(),
+            // We only have the rendered diagnostic string here (no access
+            // to the original `Span`/`SourceMap`), so byte offsets aren't
+            // recoverable; line/column is what flycheck tooling reads.
+            byte_start: 0,
+            byte_end: 0,
+            line_start: caps["ls"].parse().ok()?,
+            line_end: caps["le"].parse().ok()?,
+            column_start: caps["cs"].parse().ok()?,
+            column_end: caps["ce"].parse().ok()?,
+            is_primary,
+            suggested_replacement,
+        })
+    }
+}
+
+impl Diagnostic {
+    /// Best-effort conversion from a `Report`. Reports are already
+    /// `Serialize`, so we go through the serialized value instead of
+    /// depending on each report kind's private fields.
+    pub fn from_report(report: &Report, suggested_replacement: Option<String>) -> Option<Self> {
+        let value = serde_json::to_value(report).ok()?;
+        let inner = value.as_object()?.values().next()?;
+        let message = inner.get("message")?.as_str()?.to_owned();
+        let level = match inner.get("severity").and_then(Value::as_str) {
+            Some("Possibly") => "warning",
+            _ => "warning",
+        }
+        .to_owned();
+        // Every detector's diagnosis struct names its span field
+        // differently (`atomic` for `AtomicityViolationDiagnosis`, `recv`
+        // for `ChannelDeadlockDiagnosis`, ...); rather than hard-coding one
+        // of those names, take the first string field that parses as
+        // `span_to_diagnostic_string` output. Not every field is one: the
+        // condvar/broadcast findings leave `excluding_branch` empty, and
+        // `LockOrderInversion`'s `recv` holds the lock order.
+        let span = inner
+            .get("diagnosis")
+            .and_then(Value::as_object)?
+            .values()
+            .filter_map(Value::as_str)
+            .find_map(|source_info| DiagnosticSpan::parse(source_info, true, suggested_replacement.clone()));
+        Some(Self {
+            message,
+            level,
+            spans: span.into_iter().collect(),
+            children: Vec::new(),
+        })
+    }
+
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
@@ -0,0 +1,244 @@
+//! Machine-applicable fix suggestions for atomic-correlation findings.
+//! Suggestions are serialized in a rustfix-compatible shape (file, byte
+//! span, replacement) so that `cargo atomvchecker --fix` can apply them
+//! without re-running the whole detector pipeline.
+extern crate rustc_span;
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{FileName, Span};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub file: PathBuf,
+    pub byte_start: u32,
+    pub byte_end: u32,
+    pub replacement: String,
+}
+
+impl Suggestion {
+    fn from_span(tcx: TyCtxt<'_>, span: Span, replacement: String) -> Option<Self> {
+        let source_map = tcx.sess.source_map();
+        let file = match source_map.span_to_filename(span) {
+            FileName::Real(real) => real.local_path()?.to_path_buf(),
+            _ => return None,
+        };
+        let lo = source_map.lookup_byte_offset(span.lo());
+        let hi = source_map.lookup_byte_offset(span.hi());
+        Some(Self {
+            file,
+            byte_start: lo.pos.0,
+            byte_end: hi.pos.0,
+            replacement,
+        })
+    }
+
+    /// Rewrite a racy `atomic.load(..)` immediately followed by
+    /// `atomic.store(v, ..)` into a single `fetch_update` call, which is
+    /// the canonical fix for this class of atomicity violation.
+    ///
+    /// `store_span` is an `AtomicInfo::span`, i.e. a call terminator's
+    /// `fn_span` -- on a method call `x.store(v, o)` that covers only
+    /// `store(v, o)`, not the receiver or the `.` before it. The
+    /// replacement must therefore stand on its own as a method call with
+    /// no leading `.`, or substituting it back into that span produces
+    /// `x..fetch_update(...)`.
+    pub fn fetch_update_for_store(tcx: TyCtxt<'_>, store_span: Span) -> Option<Self> {
+        let snippet = tcx.sess.source_map().span_to_snippet(store_span).ok()?;
+        let replacement = fetch_update_replacement(&snippet)?;
+        Self::from_span(tcx, store_span, replacement)
+    }
+}
+
+/// Build the `fetch_update(...)` replacement text for a `store(v, o)`
+/// snippet (no receiver, no leading `.` -- see `fetch_update_for_store`).
+fn fetch_update_replacement(store_snippet: &str) -> Option<String> {
+    let (value, order) = split_store_args(store_snippet)?;
+    if !looks_like_ordering(order) {
+        return None;
+    }
+    Some(format!(
+        "fetch_update({order}, {order}, |_prev| Some({value}))",
+        order = order,
+        value = value,
+    ))
+}
+
+/// Split a `.store(value, order)` call's argument list on its single
+/// top-level comma, skipping commas nested inside `()`/`[]`/`{}` or string
+/// literals so a `value` like `compute(a, b)` isn't split mid-call.
+/// Returns `(value, order)` with both sides trimmed.
+fn split_store_args(snippet: &str) -> Option<(&str, &str)> {
+    let open = snippet.find('(')?;
+    let close = snippet.rfind(')')?;
+    if close <= open {
+        return None;
+    }
+    let args = &snippet[open + 1..close];
+
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut split_at = None;
+    for (i, c) in args.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                split_at = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let split_at = split_at?;
+    let value = args[..split_at].trim();
+    let order = args[split_at + 1..].trim();
+    if value.is_empty() || order.is_empty() {
+        return None;
+    }
+    Some((value, order))
+}
+
+/// Sanity-check that `order` is a plausible `std::sync::atomic::Ordering`
+/// path (e.g. `Ordering::SeqCst`, `SeqCst`), so a misdetected split (an
+/// `order` that's actually part of a multi-argument `value`) doesn't get
+/// turned into a silently-wrong `fetch_update` call.
+fn looks_like_ordering(order: &str) -> bool {
+    let last_segment = order.rsplit("::").next().unwrap_or(order);
+    matches!(
+        last_segment,
+        "SeqCst" | "AcqRel" | "Acquire" | "Release" | "Relaxed"
+    )
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FixSet(Vec<Suggestion>);
+
+impl FixSet {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, suggestion: Suggestion) {
+        self.0.push(suggestion);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.0).unwrap()
+    }
+
+    /// Apply every suggestion in place. Suggestions are applied per-file,
+    /// sorted by descending `byte_start` so earlier spans in the same file
+    /// stay valid while later ones are rewritten. Refuses the whole batch
+    /// if any two suggested spans overlap.
+    pub fn apply(&self) -> Result<(), String> {
+        let mut by_file: HashMap<&PathBuf, Vec<&Suggestion>> = HashMap::new();
+        for suggestion in &self.0 {
+            by_file.entry(&suggestion.file).or_default().push(suggestion);
+        }
+        for (file, suggestions) in by_file.iter_mut() {
+            suggestions.sort_by_key(|s| Reverse(s.byte_start));
+            for pair in suggestions.windows(2) {
+                let (later, earlier) = (pair[0], pair[1]);
+                if earlier.byte_end > later.byte_start {
+                    return Err(format!(
+                        "refusing to apply overlapping fix suggestions in {}: [{}, {}) and [{}, {})",
+                        file.display(),
+                        earlier.byte_start,
+                        earlier.byte_end,
+                        later.byte_start,
+                        later.byte_end,
+                    ));
+                }
+            }
+        }
+        for (file, suggestions) in by_file {
+            let mut content = fs::read_to_string(file).map_err(|e| e.to_string())?;
+            for suggestion in suggestions {
+                content.replace_range(
+                    suggestion.byte_start as usize..suggestion.byte_end as usize,
+                    &suggestion.replacement,
+                );
+            }
+            fs::write(file, content).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fetch_update_replacement, looks_like_ordering, split_store_args};
+
+    /// The regex this replaced split on the *first* comma, which cut
+    /// `compute(a, b)` in half; the depth-aware scanner must instead find
+    /// the one comma at call-argument depth.
+    #[test]
+    fn nested_call_value_is_not_split_early() {
+        let (value, order) = split_store_args(".store(compute(a, b), Ordering::SeqCst)").unwrap();
+        assert_eq!(value, "compute(a, b)");
+        assert_eq!(order, "Ordering::SeqCst");
+    }
+
+    #[test]
+    fn simple_value_still_splits() {
+        let (value, order) = split_store_args(".store(1, Ordering::Relaxed)").unwrap();
+        assert_eq!(value, "1");
+        assert_eq!(order, "Ordering::Relaxed");
+    }
+
+    /// A `value` containing a string literal with a comma shouldn't be
+    /// split inside the literal either.
+    #[test]
+    fn comma_inside_string_literal_is_not_split() {
+        let (value, order) = split_store_args(r#".store(lookup("a, b"), SeqCst)"#).unwrap();
+        assert_eq!(value, r#"lookup("a, b")"#);
+        assert_eq!(order, "SeqCst");
+    }
+
+    #[test]
+    fn valid_orderings_are_recognized() {
+        assert!(looks_like_ordering("Ordering::SeqCst"));
+        assert!(looks_like_ordering("SeqCst"));
+        assert!(looks_like_ordering("atomic::Ordering::Relaxed"));
+    }
+
+    #[test]
+    fn non_ordering_tail_is_rejected() {
+        assert!(!looks_like_ordering("Ordering::SeqCst, extra"));
+        assert!(!looks_like_ordering("not_an_ordering"));
+    }
+
+    /// `store_span` is a call terminator's `fn_span`, which on `x.store(v,
+    /// o)` covers only `store(v, o)` -- no receiver, no leading `.`. A
+    /// replacement built against that shape must stand alone as a bare
+    /// method call; a leading `.` here would substitute back in as
+    /// `x..fetch_update(...)`.
+    #[test]
+    fn replacement_has_no_leading_dot() {
+        let replacement = fetch_update_replacement("store(1, Ordering::Relaxed)").unwrap();
+        assert_eq!(replacement, "fetch_update(Ordering::Relaxed, Ordering::Relaxed, |_prev| Some(1))");
+        assert!(!replacement.starts_with('.'));
+    }
+}
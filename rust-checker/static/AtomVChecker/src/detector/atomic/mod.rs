@@ -17,54 +17,73 @@ extern crate rustc_middle;
 extern crate rustc_codegen_ssa;
 extern crate rustc_hir;
 extern crate rustc_index;
+extern crate rayon;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use rayon::prelude::*;
 
 use regex::Regex;
-use rustc_middle::mir::{Body, Local, Location, Place, PlaceRef, ProjectionElem};
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::mir::{BasicBlock, Body, Local, Location, Operand, Place, PlaceRef, ProjectionElem, Rvalue, Statement, StatementKind, TerminatorKind};
+use rustc_middle::ty::{Instance, TyCtxt, TyKind};
+use rustc_span::Span;
 use log::{debug, warn};
 
 
 pub mod report;
+pub mod fix;
+pub mod diagnostic;
+pub mod kani;
+use crate::detector::atomic::fix::{FixSet, Suggestion};
 use crate::analysis::callgraph::{CallGraph, InstanceId, CallGraphNode};
 use crate::analysis::controldep;
 use crate::analysis::datadep;
+use crate::analysis::postdom;
 use crate::analysis::defuse;
 use crate::analysis::pointsto::{AliasAnalysis, ConstraintNode};
 use crate::detector::atomic::report::AtomicityViolationDiagnosis;
 use crate::detector::report::{Report, ReportContent};
-use crate::interest::concurrency::atomic::{AtomicCollector, AtomicInfo, AtomicInstructions, AtomicOrd, AtomPart};
+use crate::interest::concurrency::atomic::{AtomicBacking, AtomicCollector, AtomicInfo, AtomicInstructions, AtomicOrd, AtomPart, CasForm};
 
 use petgraph::visit::IntoNodeReferences;
+use petgraph::Direction::Outgoing;
 
 pub struct CorrelationAnalyzer<'tcx> {
     tcx: TyCtxt<'tcx>,
     partner: (AtomicInfo<'tcx>, Vec<Local>),
     // interimval_map: HashMap<Local, Option<HashSet<ConstraintNode<'tcx>, BuildHasherDefault<FxHasher>>>>, // HashMap<Local, Option<&HashSet<ConstraintNode<'tcx>, BuildHasherDefault<FxHasher>>>>,
     correlations: HashSet<PlaceRef<'tcx>>,
+    /// Shared with every other `CorrelationAnalyzer` built for the same
+    /// `detect` call (see `AtomicityViolationDetector::detect`), so the
+    /// per-`DefId` memoization in `AliasAnalysis::get_or_insert_pts` is
+    /// actually reused across partners and directions instead of every
+    /// `resolve_load_collelation`/`resolve_store_collelation` call
+    /// re-solving the same body's points-to set from scratch.
+    alias_analysis: Arc<AliasAnalysis<'tcx>>,
 }
 
 impl<'tcx> CorrelationAnalyzer<'tcx> {
     pub fn new(
         tcx: TyCtxt<'tcx>,
         partner: (AtomicInfo<'tcx>, Vec<Local>),
+        alias_analysis: Arc<AliasAnalysis<'tcx>>,
     ) -> Self {
-        Self { 
+        Self {
             tcx,
             partner,
             // interimval_map: HashMap::new(),
             correlations: HashSet::new(),
+            alias_analysis,
         }
     }
 
     pub fn resolve_load_collelation(&mut self, callgraph: &CallGraph<'tcx>) {
         let (atomic_info, interim_val) = self.partner.clone();
         let inst = callgraph.index_to_instance(atomic_info.caller_instance);
-        let body = self.tcx.instance_mir(inst.unwrap().instance().def); 
+        let body = self.tcx.instance_mir(inst.unwrap().instance().def);
         let mut corrlations = HashSet::new();
-        let mut alias_analysis = AliasAnalysis::new(self.tcx); 
-        let points_to_map = alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
+        let points_to_map = self.alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
         if let Some(AtomicInstructions::CompareExchange) | Some(AtomicInstructions::ReadModifyWrite) = atomic_info.atomic_operate {
             if let Some(position) = interim_val.iter().position(|&x| x == atomic_info.atomic_place.unwrap().local) {
                 let atomic_node = ConstraintNode::Place(Place::from(atomic_info.atomic_place.unwrap().local).as_ref());
@@ -158,10 +177,9 @@ impl<'tcx> CorrelationAnalyzer<'tcx> {
     pub fn resolve_store_collelation(&mut self, callgraph: &CallGraph<'tcx>) {
         let (atomic_info, interim_val) = self.partner.clone();
         let inst = callgraph.index_to_instance(atomic_info.caller_instance);
-        let body = self.tcx.instance_mir(inst.unwrap().instance().def); 
+        let body = self.tcx.instance_mir(inst.unwrap().instance().def);
         let mut corrlations = HashSet::new();
-        let mut alias_analysis = AliasAnalysis::new(self.tcx); 
-        let points_to_map = alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
+        let points_to_map = self.alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
         if let Some(AtomicInstructions::CompareExchange) | Some(AtomicInstructions::ReadModifyWrite) = atomic_info.atomic_operate {
             if let Some(position) = interim_val.iter().position(|&x| x == atomic_info.atomic_place.unwrap().local) {
                 let atomic_node = ConstraintNode::Place(Place::from(atomic_info.atomic_place.unwrap().local).as_ref());
@@ -278,51 +296,257 @@ impl<'tcx> CorrelationAnalyzer<'tcx> {
 
 }
 
+/// Interprocedural feasibility filter for `get_factors`/`get_atomptr_factors`:
+/// answers whether two functions' atomic accesses can actually run
+/// concurrently, so the read/write cross product those two build doesn't
+/// keep pairs that can only ever execute sequentially on one thread.
+///
+/// "Thread roots" are instances passed as the closure argument to
+/// `std::thread::spawn`, `thread::Builder::spawn`, or `rayon::spawn` --
+/// found the same way `collect_atomics` finds atomic intrinsics, by
+/// matching `def_path_str_with_substs` on call terminators. A pair is kept
+/// when its two accesses are reachable from two distinct roots, or from
+/// the same root that can reach itself through a call-graph cycle (the
+/// same spawned closure running concurrently with itself). A root
+/// re-spawned inside a caller's loop is not recognized as concurrent with
+/// itself: a loop in the caller's CFG adds no call-graph cycle, so that
+/// case would take a CFG-level loop check on top of this.
+///
+/// An instance reachable from no thread root at all isn't dead code --
+/// it's code that only ever runs directly on the spawning thread (the
+/// common case being `fn main` itself touching a shared atomic that a
+/// spawned thread also touches). `is_feasible` treats such an instance as
+/// running on an implicit main-thread root distinct from every real spawn
+/// root, so it's feasible against anything reachable from an actual spawn,
+/// but not against another never-spawned instance (that pair is just
+/// ordinary sequential code on the one thread that runs it).
+struct ConcurrencyReachability {
+    /// `InstanceId` -> the set of roots that can reach it, cached here so
+    /// `is_feasible` below is an O(1) set lookup instead of a fresh
+    /// traversal per query.
+    reached_by: HashMap<InstanceId, HashSet<InstanceId>>,
+    /// Roots reachable from themselves through a call-graph cycle.
+    self_concurrent_roots: HashSet<InstanceId>,
+}
+
+impl ConcurrencyReachability {
+    fn build<'tcx>(tcx: TyCtxt<'tcx>, callgraph: &CallGraph<'tcx>) -> Self {
+        let mut reached_by: HashMap<InstanceId, HashSet<InstanceId>> = HashMap::new();
+        let mut self_concurrent_roots = HashSet::new();
+        for root in Self::thread_roots(tcx, callgraph) {
+            let mut visited = HashSet::new();
+            let mut stack = vec![root];
+            let mut revisits_root = false;
+            while let Some(node) = stack.pop() {
+                if !visited.insert(node) {
+                    continue;
+                }
+                reached_by.entry(node).or_default().insert(root);
+                for callee in callgraph.graph.neighbors_directed(node, Outgoing) {
+                    if callee == root {
+                        revisits_root = true;
+                    }
+                    stack.push(callee);
+                }
+            }
+            if revisits_root {
+                self_concurrent_roots.insert(root);
+            }
+        }
+        Self { reached_by, self_concurrent_roots }
+    }
+
+    fn thread_roots<'tcx>(tcx: TyCtxt<'tcx>, callgraph: &CallGraph<'tcx>) -> Vec<InstanceId> {
+        let re = Regex::new(r"^(std::thread::spawn|std::thread::Builder::spawn|rayon::spawn)").unwrap();
+        let mut roots = Vec::new();
+        for (index, _) in callgraph.graph.node_references() {
+            let inst = match callgraph.index_to_instance(index).unwrap() {
+                CallGraphNode::WithBody(instance) => instance,
+                CallGraphNode::WithoutBody(_) => continue,
+            };
+            let body = tcx.instance_mir(inst.def);
+            for block in body.basic_blocks.iter() {
+                let TerminatorKind::Call { func, args, .. } = &block.terminator().kind else {
+                    continue;
+                };
+                let TyKind::FnDef(def_id, substs) = func.ty(body, tcx).kind() else {
+                    continue;
+                };
+                if re.find(&tcx.def_path_str_with_substs(*def_id, substs)).is_none() {
+                    continue;
+                }
+                let Some(closure_arg) = args.get(0) else {
+                    continue;
+                };
+                let TyKind::Closure(closure_def_id, closure_substs) = closure_arg.ty(body, tcx).kind() else {
+                    continue;
+                };
+                let closure_instance = Instance::new(*closure_def_id, closure_substs);
+                if let Some(id) = callgraph.instance_to_index(closure_instance) {
+                    roots.push(id);
+                }
+            }
+        }
+        roots
+    }
+
+    /// Keep `(read_instance, write_instance)` only if the two can run
+    /// concurrently, per the rules documented on this type.
+    fn is_feasible(&self, read_instance: InstanceId, write_instance: InstanceId) -> bool {
+        let empty = HashSet::new();
+        let read_roots = self.reached_by.get(&read_instance).unwrap_or(&empty);
+        let write_roots = self.reached_by.get(&write_instance).unwrap_or(&empty);
+        // Unreachable from any spawn root: the instance only ever runs on
+        // the implicit main-thread root. Two never-spawned instances are
+        // just sequential code on that one thread; a never-spawned
+        // instance paired with anything a real spawn root reaches is the
+        // main thread racing a spawned thread, which is always feasible.
+        match (read_roots.is_empty(), write_roots.is_empty()) {
+            (true, true) => false,
+            (true, false) | (false, true) => true,
+            (false, false) => read_roots.iter().any(|r1| {
+                write_roots
+                    .iter()
+                    .any(|r2| r1 != r2 || self.self_concurrent_roots.contains(r1))
+            }),
+        }
+    }
+}
+
+/// A standalone `std::sync::atomic::fence`/`compiler_fence` call site. A
+/// Relaxed atomic op next to one of these carries the fence's ordering in
+/// practice, so `AtomicityViolationDetector::fence_covers` checks these
+/// against the atomic's own block before flagging it as "too weak" on its
+/// own -- see the ordering-candidate loops in `detect`. A `Relaxed` fence
+/// is itself a guaranteed panic, caught separately by
+/// `detect_invalid_orderings` before this struct's `ordering` is ever
+/// consulted for coverage.
+///
+/// Deliberately kept outside `AtomicInstructions`/`AtomicInfo` rather than
+/// added as a `Fence` variant there: every other `AtomicInstructions` kind
+/// takes a `self`/place argument that an `AtomicInfo` keys its analysis on
+/// (`atomic_place`, `atomic_value`, ...), but a fence call takes only an
+/// `Ordering` -- it isn't an operation *on* any one atomic variable, it
+/// orders every memory operation at that program point. Forcing it into
+/// the place-shaped `AtomicInfo` pipeline would mean fields that never
+/// apply to it; this lightweight, purpose-built record is a closer fit,
+/// consistent with how this detector already has more than one kind of
+/// "atomic-ish" fact it tracks (cf. the separate `store_buffer_participants`
+/// bookkeeping, which isn't part of `AtomicInfo` either). That global,
+/// not-per-variable scope is also why `fence_covers` below checks dominance
+/// within the whole caller function rather than correlating the fence
+/// against the atomic's own `ConstraintNode`: a real fence provides its
+/// ordering guarantee to every atomic access program-order-adjacent to it,
+/// not just ones touching the same variable.
+struct FenceInfo {
+    caller_instance: InstanceId,
+    block: BasicBlock,
+    span: Span,
+    ordering: AtomicOrd,
+}
+
+impl FenceInfo {
+    /// Found the same way `ConcurrencyReachability::thread_roots` finds
+    /// `thread::spawn` call sites: matching `def_path_str_with_substs` on
+    /// call terminators, here against `fence`/`compiler_fence` from either
+    /// `std` or `core`.
+    fn collect<'tcx>(tcx: TyCtxt<'tcx>, callgraph: &CallGraph<'tcx>) -> Vec<Self> {
+        let re = Regex::new(r"^(std|core)::sync::atomic::(fence|compiler_fence)").unwrap();
+        let mut fences = Vec::new();
+        for (index, _) in callgraph.graph.node_references() {
+            let inst = match callgraph.index_to_instance(index).unwrap() {
+                CallGraphNode::WithBody(instance) => instance,
+                CallGraphNode::WithoutBody(_) => continue,
+            };
+            let body = tcx.instance_mir(inst.def);
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                let TerminatorKind::Call { func, args, .. } = &data.terminator().kind else {
+                    continue;
+                };
+                let TyKind::FnDef(def_id, substs) = func.ty(body, tcx).kind() else {
+                    continue;
+                };
+                if re.find(&tcx.def_path_str_with_substs(*def_id, substs)).is_none() {
+                    continue;
+                }
+                let Some(ordering_place) = args.get(0).and_then(|arg| arg.place()) else {
+                    continue;
+                };
+                fences.push(FenceInfo {
+                    caller_instance: index,
+                    block,
+                    span: data.terminator().source_info.span,
+                    ordering: AtomicOrd::from_ordering(tcx, callgraph, index, body, &ordering_place),
+                });
+            }
+        }
+        fences
+    }
+}
+
+/// Which `BasicBlock` in `body` contains the call terminator at `span`,
+/// matching on span equality since every `AtomicInfo`/`FenceInfo` records
+/// the span of the call site it was collected from.
+fn block_for_span(body: &Body<'_>, span: Span) -> Option<BasicBlock> {
+    body.basic_blocks
+        .iter_enumerated()
+        .find(|(_, data)| data.terminator().source_info.span == span)
+        .map(|(block, _)| block)
+}
+
 pub struct AtomicityViolationDetector<'tcx> {
     tcx: TyCtxt<'tcx>,
+    /// When set, every `AliasAnalysis` this detector builds is backed by a
+    /// cross-session disk cache under this directory (see
+    /// `analysis::pointsto::cache::PointsToCache`), so a later run over
+    /// unchanged MIR can skip Andersen's fixpoint solve entirely.
+    output_directory: Option<PathBuf>,
 }
 
 impl<'tcx> AtomicityViolationDetector<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
-        Self { tcx }
+        Self { tcx, output_directory: None }
+    }
+
+    /// Same as `new`, but enables the disk-backed points-to cache described
+    /// on `output_directory` above.
+    pub fn new_with_output_dir(tcx: TyCtxt<'tcx>, output_directory: PathBuf) -> Self {
+        Self { tcx, output_directory: Some(output_directory) }
+    }
+
+    /// `AliasAnalysis` backed by this detector's disk cache, if any.
+    fn alias_analysis(&self) -> AliasAnalysis<'tcx> {
+        match &self.output_directory {
+            Some(dir) => AliasAnalysis::new_with_cache_dir(self.tcx, dir),
+            None => AliasAnalysis::new(self.tcx),
+        }
     }
 
+    /// Drive the per-atomic partner/points-to analysis across a rayon thread
+    /// pool, since every atomic instruction's `AtomicCollector`/`AtomPart`
+    /// walk (and the `all_data_dep_on` traversal inside it) is independent of
+    /// every other's. The points-to cache behind `alias_analysis` is
+    /// `RwLock`-guarded so it can be shared across workers instead of
+    /// rebuilding per-`DefId` points-to info redundantly per thread.
+    ///
+    /// Both loops collect through an `IndexedParallelIterator`, so the
+    /// resulting `Vec`/`HashMap` ordering matches the serial version
+    /// regardless of how rayon schedules the work, keeping report output
+    /// deterministic.
     pub fn gen_atomic_info(
-        &self, 
-        atomics: Vec<&CallGraphNode<'tcx>>, 
-        atomics_atomptr: HashMap<String ,Vec<&CallGraphNode<'tcx>>>, 
-        callgraph: &CallGraph<'tcx>
+        &self,
+        atomics: Vec<&CallGraphNode<'tcx>>,
+        atomics_atomptr: HashMap<String ,Vec<&CallGraphNode<'tcx>>>,
+        callgraph: &CallGraph<'tcx>,
+        alias_analysis: &Arc<AliasAnalysis<'tcx>>,
     ) -> (
-        Vec<Vec<(AtomicInfo<'tcx>, Vec<Local>)>>, 
-        HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>> 
-    ) { 
-        let mut atom_maps = HashMap::new();
-        // let mut atom_ptr_infos: Vec<Vec<(AtomicInfo<'tcx>, Vec<Local>)>> = Vec::new();
-        for atomic in &atomics {
-            let atomic_instance = atomic.instance();
-            let instance_id = callgraph.instance_to_index(atomic_instance).unwrap();
-            let mut atomic_collector = AtomicCollector::new(self.tcx, instance_id, atomic_instance);
-            atomic_collector.analyze(callgraph);
-            if atomic_collector.atomics.len() != 0{
-                for key in atomic_collector.atomics.into_iter() {
-                    let mut atompart_collector = AtomPart::new(self.tcx, key);
-                    atompart_collector.infer_interival(callgraph);
-
-                    let atom_map = atompart_collector.classify_atomic(callgraph);
-                    for (key, values) in atom_map {
-                        let values_cloned = values.iter()
-                          .map(|(info, locals)| (info.clone(), locals.clone()))
-                          .collect::<Vec<_>>();
-                        atom_maps.entry(key)
-                            .and_modify(|e: &mut Vec<(AtomicInfo<'tcx>, Vec<Local>)>| e.extend(values_cloned.clone()))
-                            .or_insert_with(|| values_cloned);
-                    }
-                }
-            }
-        }
-        let atom_ptr_infos = atomics_atomptr.into_iter().map(|(_, atomics)| {
-            let mut infos = Vec::new();
-            for atomic in &*atomics {
+        Vec<Vec<(AtomicInfo<'tcx>, Vec<Local>)>>,
+        HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>
+    ) {
+        let per_atomic_maps: Vec<HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>> = atomics
+            .par_iter()
+            .map(|atomic| {
+                let mut local_map: HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>> = HashMap::new();
                 let atomic_instance = atomic.instance();
                 let instance_id = callgraph.instance_to_index(atomic_instance).unwrap();
                 let mut atomic_collector = AtomicCollector::new(self.tcx, instance_id, atomic_instance);
@@ -330,20 +554,62 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                 if atomic_collector.atomics.len() != 0 {
                     for key in atomic_collector.atomics.into_iter() {
                         let mut atompart_collector = AtomPart::new(self.tcx, key);
-                        atompart_collector.infer_atomptr_interival(callgraph);
-                        infos.extend(atompart_collector.partner);
+                        atompart_collector.infer_interival(callgraph);
+
+                        let atom_map = atompart_collector.classify_atomic(callgraph, alias_analysis);
+                        for (key, values) in atom_map {
+                            local_map.entry(key).or_insert_with(Vec::new).extend(values);
+                        }
                     }
                 }
+                local_map
+            })
+            .collect();
+
+        let mut atom_maps: HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>> = HashMap::new();
+        for local_map in per_atomic_maps {
+            for (key, values) in local_map {
+                atom_maps.entry(key).or_insert_with(Vec::new).extend(values);
             }
-            infos
-        }).collect();
+        }
+
+        // Sort into a `Vec` first so the parallel map below has a stable,
+        // thread-independent order to collect back into.
+        let mut atomptr_groups: Vec<(String, Vec<&CallGraphNode<'tcx>>)> =
+            atomics_atomptr.into_iter().collect();
+        atomptr_groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let atom_ptr_infos = atomptr_groups
+            .into_par_iter()
+            .map(|(_, atomics)| {
+                let mut infos = Vec::new();
+                for atomic in &*atomics {
+                    let atomic_instance = atomic.instance();
+                    let instance_id = callgraph.instance_to_index(atomic_instance).unwrap();
+                    let mut atomic_collector = AtomicCollector::new(self.tcx, instance_id, atomic_instance);
+                    atomic_collector.analyze(callgraph);
+                    if atomic_collector.atomics.len() != 0 {
+                        for key in atomic_collector.atomics.into_iter() {
+                            let mut atompart_collector = AtomPart::new(self.tcx, key);
+                            atompart_collector.infer_atomptr_interival(callgraph, alias_analysis);
+                            infos.extend(atompart_collector.partner);
+                        }
+                    }
+                }
+                infos
+            })
+            .collect();
         (atom_ptr_infos, atom_maps)
     }
 
     
     /// Collect atomic APIs.
     /// Rerturn the atomicInfos.
-    fn collect_atomics(&self, callgraph: &CallGraph<'tcx>) -> (Vec<Vec<(AtomicInfo<'tcx>, Vec<Local>)>>, HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>) { // ProjectionElem<Local, Ty<'tcx>>
+    fn collect_atomics(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        alias_analysis: &Arc<AliasAnalysis<'tcx>>,
+    ) -> (Vec<Vec<(AtomicInfo<'tcx>, Vec<Local>)>>, HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>) { // ProjectionElem<Local, Ty<'tcx>>
         let mut atomics = Vec::new();
         let mut ptr_type = String::new();
         let mut atomics_atomptr = HashMap::new();
@@ -353,23 +619,36 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                 CallGraphNode::WithoutBody(instance) => instance,
             };
             let func_name = self.tcx.def_path_str_with_substs(inst.def_id(), inst.substs);
-            let re = Regex::new(r"^(std|core)::sync::atomic::((AtomicPtr)(::<(.*?)>)?|(Atomic[A-Za-z]+)(::<(.*?)>)?)(::)?(load|store|swap|compare_exchange(_weak)?|fetch_(and|add|sub|or|update|max|xor)|compare_and_swap)").unwrap();
-            
+            // fetch_(byte_add|byte_sub|ptr_add|ptr_sub) are the strict-provenance
+            // AtomicPtr arithmetic ops, fetch_nand the stdlib RMW this list had
+            // otherwise missed -- both already classified as `ReadModifyWrite`
+            // by `AtomicInstructions::from_path`, but never reached that
+            // classification because this discovery regex never matched their
+            // call sites in the first place.
+            let re = Regex::new(r"^(std|core)::sync::atomic::((AtomicPtr)(::<(.*?)>)?|(Atomic[A-Za-z]+)(::<(.*?)>)?)(::)?(load|store|swap|compare_exchange(_weak)?|fetch_(and|add|sub|or|update|max|xor|nand|byte_add|byte_sub|ptr_add|ptr_sub)|compare_and_swap)").unwrap();
+            // The third-party `atomic` crate's generic `Atomic<T>` wrapper --
+            // no AtomicPtr-style split exists for it (there's no dedicated
+            // pointer specialization the way `std`/`core` have `AtomicPtr`),
+            // so every match goes in the plain `atomics` bucket below.
+            let third_party_re = Regex::new(r"^atomic::Atomic::<.*?>(::)?(load|store|swap|compare_exchange(_weak)?|fetch_(and|add|sub|or|update|max|xor|nand)|compare_and_swap)$").unwrap();
+
             // Identify atomic operations and distinguish between AtomicPtr and non-AtomicPtr
             if let Some(caps) = re.captures(&func_name) {
-            if caps.get(3).is_some() { 
+            if caps.get(3).is_some() {
                 // The operation is an AtomicPtr operation
-                if let Some(specific_type) = caps.get(5) { 
+                if let Some(specific_type) = caps.get(5) {
                     ptr_type = specific_type.as_str().to_string();
                 }
                 atomics_atomptr.entry(ptr_type.clone()).or_insert_with(Vec::new).push(node);
-            } else if caps.get(6).is_some() { 
+            } else if caps.get(6).is_some() {
                     // The operation is a non-AtomicPtr operation
                     atomics.push(node);
                 }
+            } else if third_party_re.is_match(&func_name) {
+                atomics.push(node);
             }
         }
-        self.gen_atomic_info(atomics, atomics_atomptr, callgraph)
+        self.gen_atomic_info(atomics, atomics_atomptr, callgraph, alias_analysis)
     }
 
     // pub fn is_unsafe_write(
@@ -415,7 +694,10 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
     //         return false;
     //     }
 
-    pub fn get_factors(atomic_infos: Vec<(AtomicInfo<'tcx>, Vec<Local>)>) -> Vec<((AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>), (AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>))> {
+    pub fn get_factors(
+        atomic_infos: Vec<(AtomicInfo<'tcx>, Vec<Local>)>,
+        reachability: &ConcurrencyReachability,
+    ) -> Vec<((AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>), (AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>))> {
         // Classify atomic operations
         let mut read_atomics = Vec::new();
         let mut write_atomics = Vec::new();
@@ -450,44 +732,49 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
         //     }
         // }
 
-        // Generate atomic read-write pairs
+        // Generate atomic read-write pairs, keeping only those the two
+        // accesses' enclosing functions can actually run concurrently --
+        // see `ConcurrencyReachability`.
         let mut factors = Vec::new();
         for read_atomic in read_atomics.clone() {
             for write_atomic in write_atomics.clone() {
-                // if &read_atomic.0.caller_instance != &write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_atomic.0.caller_instance, write_atomic.0.caller_instance) {
                     factors.push((read_atomic.clone(), write_atomic.clone()));
-                // }
+                }
             }
         }
 
         for read_atomic in read_atomics.clone() {
             for read_write_atomic in read_write_atomics.clone() {
-                // if  &read_atomic.0.caller_instance != &read_write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_atomic.0.caller_instance, read_write_atomic.0.caller_instance) {
                     factors.push((read_atomic.clone(), read_write_atomic.clone()));
-                // }
+                }
             }
         }
 
         for read_write_atomic in read_write_atomics.clone() {
             for write_atomic in write_atomics.clone() {
-                // if &read_write_atomic.0.caller_instance != &write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_write_atomic.0.caller_instance, write_atomic.0.caller_instance) {
                     factors.push((read_write_atomic.clone(), write_atomic.clone()));
-                // }
+                }
             }
         }
 
         for read_atomic in read_write_atomics.clone() {
             for write_atomic in read_write_atomics.clone() {
-                // if read_atomic.0.caller_instance != write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_atomic.0.caller_instance, write_atomic.0.caller_instance) {
                     factors.push((read_atomic.clone(), write_atomic.clone()));
-                // }
+                }
             }
         }
         return factors;
     }
 
 
-    pub fn get_atomptr_factors(atomic_infos: Vec<(AtomicInfo<'tcx>, Vec<Local>)>) -> Vec<((AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>), (AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>))> {
+    pub fn get_atomptr_factors(
+        atomic_infos: Vec<(AtomicInfo<'tcx>, Vec<Local>)>,
+        reachability: &ConcurrencyReachability,
+    ) -> Vec<((AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>), (AtomicInfo<'tcx>, Vec<rustc_middle::mir::Local>))> {
         // Classify atomic operations
         let mut read_atomics = Vec::new();
         let mut write_atomics = Vec::new();
@@ -512,54 +799,132 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
             }
         }
 
-        // Generate atomic read-write pairs
+        // Generate atomic read-write pairs, keeping only those the two
+        // accesses' enclosing functions can actually run concurrently --
+        // see `ConcurrencyReachability`.
         let mut factors = Vec::new();
         for read_atomic in read_atomics.clone() {
             for write_atomic in write_atomics.clone() {
-                // if &read_atomic.0.caller_instance != &write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_atomic.0.caller_instance, write_atomic.0.caller_instance) {
                     factors.push((read_atomic.clone(), write_atomic.clone()));
-                // }
+                }
             }
         }
 
         for read_atomic in read_atomics.clone() {
             for read_write_atomic in read_write_atomics.clone() {
-                // if  &read_atomic.0.caller_instance != &read_write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_atomic.0.caller_instance, read_write_atomic.0.caller_instance) {
                     factors.push((read_atomic.clone(), read_write_atomic.clone()));
-                // }
+                }
             }
         }
 
         for read_write_atomic in read_write_atomics.clone() {
             for write_atomic in write_atomics.clone() {
-                // if &read_write_atomic.0.caller_instance != &write_atomic.0.caller_instance {
+                if reachability.is_feasible(read_write_atomic.0.caller_instance, write_atomic.0.caller_instance) {
                     factors.push((read_write_atomic.clone(), write_atomic.clone()));
-                // }
+                }
             }
         }
 
         for read_atomic in read_write_atomics.clone() {
             for write_atomic in read_write_atomics.clone() {
+                if reachability.is_feasible(read_atomic.0.caller_instance, write_atomic.0.caller_instance) {
                     factors.push((read_atomic.clone(), write_atomic.clone()));
+                }
             }
         }
         return factors;
     }
 
 
-    /// Detect atomicity violation intra-procedurally and returns bug report.
+    /// Detect atomicity violation intra-procedurally and returns bug report
+    /// together with machine-applicable fix suggestions for the findings
+    /// that admit one (currently: a racy load+store pair on the same
+    /// atomic, fixable by folding it into a single `fetch_update`).
     pub fn detect<'a>(
         &mut self,
         callgraph: &'a CallGraph<'tcx>,
-    ) -> Vec<Report> {
+    ) -> (Vec<Report>, FixSet) {
         let mut reports = Vec::new();
-        
-        let (atomptr_infos, atom_infos) = self.collect_atomics(callgraph);
+        let mut fixes = FixSet::new();
+
+        // Shared by every `CorrelationAnalyzer` and `AtomPart` points-to
+        // lookup below, so `AliasAnalysis::get_or_insert_pts`'s per-`DefId`
+        // memoization is actually reused across the whole crate instead of
+        // each analyzer re-solving the same body's points-to set.
+        let alias_analysis = Arc::new(self.alias_analysis());
+
+        let (mut atomptr_infos, mut atom_infos) = self.collect_atomics(callgraph, &alias_analysis);
+
+        // Standalone `fence`/`compiler_fence` calls, collected up front since
+        // the invalid-ordering pass right below also checks these.
+        let fences = FenceInfo::collect(self.tcx, callgraph);
+
+        // 0a、Detect orderings the stdlib panics on outright -- e.g. a
+        // `Release` `Load`, or a `Relaxed` fence -- rather than the
+        // "stronger/weaker than its pair" heuristics below, which assume a
+        // legal ordering to begin with. These are certain bugs independent
+        // of any correlation, so they're reported with definite severity
+        // and the offending ops are dropped before the correlation passes
+        // ever see them, since "too strong/too weak relative to its pair"
+        // is meaningless once the ordering itself is already invalid.
+        reports.extend(self.detect_invalid_orderings(&atom_infos, &atomptr_infos, &fences));
+        Self::retain_valid_orderings(&mut atom_infos, &mut atomptr_infos);
+
+        // Grouped infos are consumed by the ordering-correlation passes below;
+        // the weakening/weak-CAS passes only need to read them, so keep their
+        // own copies.
+        let atom_infos_for_weakening = atom_infos.clone();
+        let atomptr_infos_for_weak_cas = atomptr_infos.clone();
+
+        // Built once and shared by every atomic variable's factor list
+        // below: which functions can run concurrently with which, so
+        // `get_factors`/`get_atomptr_factors` can drop read/write pairs
+        // that can only ever execute sequentially on the same thread.
+        let reachability = ConcurrencyReachability::build(self.tcx, callgraph);
+
+        // Atomics that take part in a store-buffer/IRIW shape across two
+        // different variables; a per-variable Release/Acquire pairing can't
+        // rule out the reordering this shape depends on, so these need
+        // SeqCst regardless of what the per-variable analysis below
+        // concludes.
+        let store_buffer_participants = Self::store_buffer_participants(&atom_infos_for_weakening, &reachability);
+
+        // 0、Surface third-party wrapper atomics (`atomic::Atomic<T>`; calls
+        // on `crossbeam_utils::atomic::AtomicCell<T>` are not collected as
+        // atomics, so they never reach this check) whose concrete `T`
+        // doesn't fit a native lock-free width: the wrapper silently falls
+        // back to a global spinlock, so the `Ordering` argument is accepted
+        // but has no bearing on the actual (lock-based) synchronization --
+        // easy to miss reading code that otherwise looks like it's using a
+        // real atomic. Informational rather than "Possibly", since this is
+        // read straight off the type rather than inferred.
+        for (info, _) in atom_infos_for_weakening.values().flatten().chain(atomptr_infos_for_weak_cas.iter().flatten()) {
+            if info.backing != AtomicBacking::Spinlock {
+                continue;
+            }
+            let diagnosis = AtomicityViolationDiagnosis {
+                atomic: info.source_info.clone(),
+            };
+            let report_content = ReportContent::new(
+                "LockBasedAtomicFallback".to_owned(),
+                "Informational".to_owned(),
+                diagnosis,
+                "This \"atomic\" operation's value type doesn't fit a native lock-free width, so the \
+                 wrapper routes it through a global spinlock instead of a real atomic instruction; the \
+                 memory-ordering argument is accepted but has no effect on the actual (lock-based) \
+                 synchronization."
+                    .to_owned(),
+            );
+            reports.push(Report::AtomicCorrelationViolation(report_content));
+        }
+
         // 1、Detect critial-state inconsistent update bug
         for (_, atom_infos) in atom_infos {
             // Analyze all atomic operations for each atomic variable
             let mut ordering_candidates = HashMap::new();
-            let factors = Self::get_factors(atom_infos.clone());
+            let factors = Self::get_factors(atom_infos.clone(), &reachability);
             for (factor_load, factor_write) in factors {
                 let instance = callgraph
                     .index_to_instance(factor_load.clone().0.caller_instance)
@@ -590,9 +955,9 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                         }
                 }
                 if !control_dep.is_empty() {
-                    let mut analyzer_load = CorrelationAnalyzer::new(self.tcx, factor_load.clone());
+                    let mut analyzer_load = CorrelationAnalyzer::new(self.tcx, factor_load.clone(), alias_analysis.clone());
                     analyzer_load.resolve_load_collelation(callgraph);
-                    let mut analyzer_store = CorrelationAnalyzer::new(self.tcx, factor_write.clone());
+                    let mut analyzer_store = CorrelationAnalyzer::new(self.tcx, factor_write.clone(), alias_analysis.clone());
                     analyzer_store.resolve_store_collelation(callgraph);
                     if analyzer_load.correlations.is_empty() && !analyzer_store.correlations.is_empty() {
                         // let mut load_ordering = HashSet::new();
@@ -679,6 +1044,26 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
             debug!("atomic correlations: {}: {}", candidates, num);
             
             for (atomic, ordering_condidates) in ordering_candidates {
+                if store_buffer_participants.contains(&atomic) {
+                    if atomic.ordering[0] < AtomicOrd::SeqCst {
+                        let diagnosis = AtomicityViolationDiagnosis {
+                            atomic: atomic.source_info.clone(),
+                        };
+                        let report_content = ReportContent::new(
+                            "SeqCstRequired".to_owned(),
+                            "Possibly".to_owned(),
+                            diagnosis,
+                            "This atomic takes part in a store-to-X-then-load-from-Y pattern with a \
+                             symmetric store-to-Y-then-load-from-X pattern elsewhere (the classic \
+                             store-buffer/IRIW shape); only a global total order rules out both loads \
+                             missing the other side's write, so SeqCst is required here rather than a \
+                             per-variable Release/Acquire pairing."
+                                .to_owned(),
+                        );
+                        reports.push(Report::AtomicCorrelationViolation(report_content));
+                    }
+                    continue;
+                }
                 let ordering = calculate_min_ordering(&ordering_condidates);
                 if let Some(AtomicInstructions::Load) | Some(AtomicInstructions::Store) | Some(AtomicInstructions::ReadModifyWrite)= atomic.atomic_operate {
                     // e.g. fetch_add 
@@ -694,12 +1079,12 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                                 format!("Using an atomic operation with a stronger memory ordering than necessary can lead to unnecessary performance overhead. Using AcqRel is sufficient to ensure the correctness of the program."),
                             );
                             reports.push(Report::AtomicCorrelationViolation(report_content));
-                        } else if atomic.ordering[0] < AtomicOrd::AcqRel {
+                        } else if atomic.ordering[0] < AtomicOrd::AcqRel && !self.fence_covers(&atomic, &fences, callgraph) {
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 format!("Using an atomic operation with a weaker memory ordering than necessary can lead to an inconsistent memory state. Using AcqRel is sufficient to ensure the program's correctness."),
@@ -718,28 +1103,35 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                                 format!("Using an atomic operation with a stronger memory ordering than necessary can lead to unnecessary performance overhead. Using {:?} is sufficient to ensure the correctness of the program", ordering[0]),
                             );
                             reports.push(Report::AtomicCorrelationViolation(report_content));
-                        } else if atomic.ordering[0] < ordering[0] {
+                        } else if atomic.ordering[0] < ordering[0] && !self.fence_covers(&atomic, &fences, callgraph) {
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 format!("Using an atomic operation with a weaker memory ordering than necessary can lead to an inconsistent memory state. Using {:?} is sufficient to ensure the program's correctness.", ordering[0]),
                             );
                             reports.push(Report::AtomicCorrelationViolation(report_content));
+                            if let Some(AtomicInstructions::Store) = atomic.atomic_operate {
+                                if let Some(suggestion) = Suggestion::fetch_update_for_store(self.tcx, atomic.span) {
+                                    fixes.push(suggestion);
+                                }
+                            }
                         }
                     }
                 } else {
                     // ordering == Release & Acquire
                     if ordering.len() == 2 {
-                        if atomic.ordering[0] < AtomicOrd::AcqRel || atomic.ordering[1] < AtomicOrd::Acquire {
+                        if (atomic.ordering[0] < AtomicOrd::AcqRel || atomic.ordering[1] < AtomicOrd::Acquire)
+                            && !self.fence_covers(&atomic, &fences, callgraph)
+                        {
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 "Using an atomic compare_exchange operation with a weaker memory ordering than necessary can lead to an inconsistent memory state, Using AcqRel and Acquire is sufficient to ensure the correctness of the program"
@@ -760,12 +1152,12 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                             reports.push(Report::AtomicCorrelationViolation(report_content));
                         }
                     } else if ordering.len() == 1 {
-                         if atomic.ordering[0] < ordering[0]  { // || atomic.ordering[1] < ordering[0]
+                         if atomic.ordering[0] < ordering[0] && !self.fence_covers(&atomic, &fences, callgraph) { // || atomic.ordering[1] < ordering[0]
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 format!("Using an atomic operation with a weaker memory ordering than necessary can lead to an inconsistent memory state. Using {:?} is sufficient to ensure the program's correctness.", ordering[0]),
@@ -792,17 +1184,16 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
         for atomptr_info in atomptr_infos {
             // Analyze all atomic operations for each atomicptr variable
             let mut ordering_candidates = HashMap::new();
-            let factors = Self::get_atomptr_factors(atomptr_info.clone());
+            let factors = Self::get_atomptr_factors(atomptr_info.clone(), &reachability);
             for (factor_load, factor_write) in factors {
 
-                let mut analyzer_load = CorrelationAnalyzer::new(self.tcx, factor_load.clone());
+                let mut analyzer_load = CorrelationAnalyzer::new(self.tcx, factor_load.clone(), alias_analysis.clone());
                 analyzer_load.resolve_atomptr_load_collelation();
-                let mut analyzer_store = CorrelationAnalyzer::new(self.tcx, factor_write.clone());
+                let mut analyzer_store = CorrelationAnalyzer::new(self.tcx, factor_write.clone(), alias_analysis.clone());
                 analyzer_store.resolve_atomptr_store_collelation();
 
                 let inst = callgraph.index_to_instance(factor_write.0.caller_instance);
-                let body = self.tcx.instance_mir(inst.unwrap().instance().def); 
-                let mut alias_analysis = AliasAnalysis::new(self.tcx); 
+                let body = self.tcx.instance_mir(inst.unwrap().instance().def);
                 let points_to_map = alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
                 for correlation in analyzer_store.correlations {
                     let node = ConstraintNode::Place(correlation);
@@ -842,7 +1233,22 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
 
                                     let mut store_ordering = HashSet::new();
                                     store_ordering.insert(AtomicOrd::Release);
-    
+
+                                    ordering_candidates.entry(factor_write.clone().0).and_modify(|ordering_result: &mut HashSet<AtomicOrd>| {
+                                        ordering_result.extend(store_ordering.iter().clone());
+                                    }).or_insert(store_ordering);
+                                },
+                                ConstraintNode::SmartPointer(_) | ConstraintNode::ParameterInto(_) => {
+                                    // The pointer being published here (including a byte/offset RMW
+                                    // like `fetch_ptr_add`/`fetch_byte_add` that produces a pointer
+                                    // as a side effect of what looks like plain arithmetic) is later
+                                    // converted to a smart pointer or handed to another instance,
+                                    // i.e. it's heuristically dereferenced downstream -- same signal
+                                    // the load-side correlation below already uses to require
+                                    // Acquire, mirrored here as Release on the publishing side.
+                                    let mut store_ordering = HashSet::new();
+                                    store_ordering.insert(AtomicOrd::Release);
+
                                     ordering_candidates.entry(factor_write.clone().0).and_modify(|ordering_result: &mut HashSet<AtomicOrd>| {
                                         ordering_result.extend(store_ordering.iter().clone());
                                     }).or_insert(store_ordering);
@@ -901,9 +1307,29 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
             debug!("atomic correlations: {}: {}", candidates, num);
 
             for (atomic, ordering_condidates) in &ordering_candidates{
+                if store_buffer_participants.contains(atomic) {
+                    if atomic.ordering[0] < AtomicOrd::SeqCst {
+                        let diagnosis = AtomicityViolationDiagnosis {
+                            atomic: atomic.source_info.clone(),
+                        };
+                        let report_content = ReportContent::new(
+                            "SeqCstRequired".to_owned(),
+                            "Possibly".to_owned(),
+                            diagnosis,
+                            "This atomic takes part in a store-to-X-then-load-from-Y pattern with a \
+                             symmetric store-to-Y-then-load-from-X pattern elsewhere (the classic \
+                             store-buffer/IRIW shape); only a global total order rules out both loads \
+                             missing the other side's write, so SeqCst is required here rather than a \
+                             per-variable Release/Acquire pairing."
+                                .to_owned(),
+                        );
+                        reports.push(Report::AtomicCorrelationViolation(report_content));
+                    }
+                    continue;
+                }
                 let ordering = calculate_min_ordering(&ordering_condidates);
-                if let Some(AtomicInstructions::Load) 
-                    | Some(AtomicInstructions::Store) 
+                if let Some(AtomicInstructions::Load)
+                    | Some(AtomicInstructions::Store)
                     | Some(AtomicInstructions::ReadModifyWrite) = atomic.atomic_operate {
                     if ordering.len() == 2 {
                         if atomic.ordering[0] > AtomicOrd::AcqRel {
@@ -917,12 +1343,12 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                                 format!("Using an atomic operation with a stronger memory ordering than necessary can lead to unnecessary performance overhead. Using AcqRel is sufficient to ensure the correctness of the program."),
                             );
                             reports.push(Report::AtomicCorrelationViolation(report_content));
-                        } else if atomic.ordering[0] < AtomicOrd::AcqRel {
+                        } else if atomic.ordering[0] < AtomicOrd::AcqRel && !self.fence_covers(atomic, &fences, callgraph) {
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 format!("Using an atomic operation with a weaker memory ordering than necessary can lead to an inconsistent memory state. Using AcqRel is sufficient to ensure the program's correctness."),
@@ -941,12 +1367,12 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                                 format!("Using an atomic operation with a stronger memory ordering than necessary can lead to unnecessary performance overhead. Using {:?} is sufficient to ensure the correctness of the program", ordering[0]),
                             );
                             reports.push(Report::AtomicCorrelationViolation(report_content));
-                        } else if atomic.ordering[0] < ordering[0] {
+                        } else if atomic.ordering[0] < ordering[0] && !self.fence_covers(atomic, &fences, callgraph) {
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 format!("Using an atomic operation with a weaker memory ordering than necessary can lead to an inconsistent memory state. Using {:?} is sufficient to ensure the program's correctness.", ordering[0]),
@@ -957,12 +1383,14 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                 } else {
                     // ordering == Release & Acquire
                     if ordering.len() == 2 {
-                        if atomic.ordering[0] < AtomicOrd::AcqRel || atomic.ordering[1] < AtomicOrd::Acquire {
+                        if (atomic.ordering[0] < AtomicOrd::AcqRel || atomic.ordering[1] < AtomicOrd::Acquire)
+                            && !self.fence_covers(atomic, &fences, callgraph)
+                        {
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 "Using an atomic compare_exchange operation with a weaker memory ordering than necessary can lead to an inconsistent memory state, Using AcqRel and Acquire is sufficient to ensure the correctness of the program"
@@ -983,12 +1411,12 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                             reports.push(Report::AtomicCorrelationViolation(report_content));
                         }
                     } else if ordering.len() == 1 {
-                         if atomic.ordering[0] < ordering[0] { //  || atomic.ordering[1] < ordering[0]
+                         if atomic.ordering[0] < ordering[0] && !self.fence_covers(atomic, &fences, callgraph) { //  || atomic.ordering[1] < ordering[0]
                             let diagnosis = AtomicityViolationDiagnosis {
                                 atomic: atomic.source_info.clone(),
                             };
                             let report_content = ReportContent::new(
-                                "AtimicCorrelationViolation".to_owned(),
+                                "InsufficientMemoryOrdering".to_owned(),
                                 "Possibly".to_owned(),
                                 diagnosis,
                                 format!("Using an atomic operation with a weaker memory ordering than necessary can lead to an inconsistent memory state. Using {:?} is sufficient to ensure the program's correctness.", ordering[0]),
@@ -1010,8 +1438,806 @@ impl<'tcx> AtomicityViolationDetector<'tcx> {
                 }
             }
         }
+
+        // 3、Detect Release/Acquire weakening between a publishing store/RMW
+        // and the load/CAS that is meant to observe its write.
+        reports.extend(Self::detect_ordering_weakening(&atom_infos_for_weakening, &reachability));
+
+        // 4、Detect a compare_exchange_weak call that isn't on any retry loop
+        let weak_cas_infos = atom_infos_for_weakening
+            .values()
+            .flatten()
+            .chain(atomptr_infos_for_weak_cas.iter().flatten())
+            .map(|(info, _)| info);
+        reports.extend(Self::detect_weak_cas_outside_loop(weak_cas_infos));
+
+        // 4b、Conversely, detect a strong compare_exchange call site that
+        // lies on a retry loop: the loop already retries on Err, so the
+        // strong form's own implicit retry is redundant there.
+        let strong_cas_infos = atom_infos_for_weakening
+            .values()
+            .flatten()
+            .chain(atomptr_infos_for_weak_cas.iter().flatten())
+            .map(|(info, _)| info);
+        reports.extend(self.detect_strong_cas_in_retry_loop(strong_cas_infos, callgraph));
+
+        // 5、Detect an ABA hazard on an AtomicPtr CAS-retry loop.
+        reports.extend(self.detect_aba_hazard(&atomptr_infos_for_weak_cas, callgraph, &alias_analysis));
+
+        // 6、Confirm candidate RMW/CAS atomicity violations against a
+        // bounded-model-checking harness, since the points-to/data-dep
+        // pairing above over-approximates which locals are associated with
+        // the atomic.
+        reports.extend(self.detect_rmw_via_model_checking(&atom_infos_for_weakening, callgraph));
+
+        // 7、Flag a `Relaxed` load that can observe a `SeqCst`/`Release`
+        // write from a concurrently-reachable thread with no recognized
+        // synchronizes-with edge (a dominating `.join()`, or an earlier,
+        // stronger load on the same atomic) in between.
+        reports.extend(self.detect_stale_relaxed_read(&atom_infos_for_weakening, &reachability, callgraph));
+
+        // 8、Flag a `fence` whose ordering is already fully subsumed by
+        // every atomic op in its own function, making it pure overhead.
+        reports.extend(self.detect_redundant_fence(&atom_infos_for_weakening, &fences));
+
+        // 9、Flag a `Clone` impl that constructs a fresh `Atomic*` rather
+        // than sharing the one it's cloning from -- silently creates
+        // independent counters instead of the shared state an `Arc` would
+        // give.
+        reports.extend(self.detect_atomic_cloned_by_value(callgraph));
+
+        (reports, fixes)
+    }
+
+    /// Why `info`'s own ordering is one the stdlib rejects outright, or
+    /// `None` if it's legal. A plain `Load` only accepts `Relaxed`/
+    /// `Acquire`/`SeqCst` (`Release`/`AcqRel` panic, since there's nothing
+    /// to release); a plain `Store` only accepts `Relaxed`/`Release`/
+    /// `SeqCst` (`Acquire`/`AcqRel` panic, since there's nothing to
+    /// acquire). A `CompareExchange`'s failure ordering (`ordering[1]`)
+    /// can never be `Release`/`AcqRel` (a failed comparison only performs a
+    /// load), and can't be stronger than the success ordering
+    /// (`ordering[0]`) -- `partial_cmp` already returns `None` for the one
+    /// incomparable pair that can reach here (`Acquire` vs `Release`), so
+    /// that pairing isn't flagged here for lack of proof either way.
+    /// `Dynamic` is never flagged -- a caller-controlled ordering can't be
+    /// judged invalid here, only at the runtime value it happens to take.
+    /// Plain `ReadModifyWrite` (`fetch_add` and friends) takes a single
+    /// ordering with no restricted subset, so it's never flagged here.
+    fn invalid_ordering_explanation(info: &AtomicInfo<'tcx>) -> Option<String> {
+        match info.atomic_operate {
+            Some(AtomicInstructions::Load) if matches!(info.ordering.first(), Some(AtomicOrd::Release) | Some(AtomicOrd::AcqRel)) => {
+                Some(
+                    "This load uses a memory ordering the standard library doesn't accept for a \
+                     Load (Release/AcqRel imply a store, which a Load never performs) -- this \
+                     panics at runtime every time this call site runs, regardless of any \
+                     correlation with another atomic."
+                        .to_owned(),
+                )
+            }
+            Some(AtomicInstructions::Store) if matches!(info.ordering.first(), Some(AtomicOrd::Acquire) | Some(AtomicOrd::AcqRel)) => {
+                Some(
+                    "This store uses a memory ordering the standard library doesn't accept for a \
+                     Store (Acquire/AcqRel imply a load, which a Store never performs) -- this \
+                     panics at runtime every time this call site runs, regardless of any \
+                     correlation with another atomic."
+                        .to_owned(),
+                )
+            }
+            Some(AtomicInstructions::CompareExchange) if info.ordering.len() == 2 => {
+                let (success, failure) = (info.ordering[0], info.ordering[1]);
+                if matches!(failure, AtomicOrd::Release | AtomicOrd::AcqRel) {
+                    Some(format!(
+                        "This compare_exchange's failure ordering is {:?}, but a failed comparison \
+                         only performs a load -- Release/AcqRel imply a store that never happens \
+                         on that path; Acquire or Relaxed is the strongest legal failure ordering \
+                         here.",
+                        failure
+                    ))
+                } else if failure.partial_cmp(&success) == Some(Ordering::Greater) {
+                    Some(format!(
+                        "This compare_exchange's failure ordering ({:?}) is stronger than its \
+                         success ordering ({:?}), which the standard library doesn't allow -- the \
+                         failure path can only be as strong as, or weaker than, the success path.",
+                        failure, success
+                    ))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn invalid_ordering_report(source_info: String, explanation: String) -> Report {
+        let diagnosis = AtomicityViolationDiagnosis { atomic: source_info };
+        Report::AtomicCorrelationViolation(ReportContent::new("InvalidAtomicOrdering".to_owned(), "Confirmed".to_owned(), diagnosis, explanation))
+    }
+
+    /// Scan every collected atomic and fence for an ordering the stdlib
+    /// panics on outright -- see `invalid_ordering_explanation` for the
+    /// `Load`/`Store`/`CompareExchange` rules; a fence additionally panics
+    /// on `Relaxed`, since a fence's entire purpose is the ordering it
+    /// provides. Unlike the stronger/weaker heuristics below, these fire
+    /// with certainty: the stdlib's own `assert!` makes this a guaranteed
+    /// panic, not a judgment call.
+    fn detect_invalid_orderings(
+        &self,
+        atom_infos: &HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>,
+        atomptr_infos: &[Vec<(AtomicInfo<'tcx>, Vec<Local>)>],
+        fences: &[FenceInfo],
+    ) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for (info, _) in atom_infos.values().flatten().chain(atomptr_infos.iter().flatten()) {
+            if let Some(explanation) = Self::invalid_ordering_explanation(info) {
+                reports.push(Self::invalid_ordering_report(info.source_info.clone(), explanation));
+            }
+        }
+        for fence in fences {
+            if fence.ordering != AtomicOrd::Relaxed {
+                continue;
+            }
+            let source_info = self.tcx.sess.source_map().span_to_diagnostic_string(fence.span);
+            reports.push(Self::invalid_ordering_report(
+                source_info,
+                "fence/compiler_fence only accepts Acquire, Release, AcqRel, or SeqCst -- Relaxed \
+                 gives the fence nothing to order and panics at runtime every time this call site \
+                 runs."
+                    .to_owned(),
+            ));
+        }
+        reports
+    }
+
+    /// Drop every `(AtomicInfo, Vec<Local>)` entry `invalid_ordering_explanation`
+    /// flags, so the stronger/weaker correlation passes that follow never
+    /// see an op whose ordering is already a guaranteed panic -- there's no
+    /// meaningful "too strong/too weak relative to its pair" verdict for an
+    /// ordering the stdlib would never actually let run.
+    fn retain_valid_orderings(
+        atom_infos: &mut HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>,
+        atomptr_infos: &mut [Vec<(AtomicInfo<'tcx>, Vec<Local>)>],
+    ) {
+        for infos in atom_infos.values_mut() {
+            infos.retain(|(info, _)| Self::invalid_ordering_explanation(info).is_none());
+        }
+        for infos in atomptr_infos.iter_mut() {
+            infos.retain(|(info, _)| Self::invalid_ordering_explanation(info).is_none());
+        }
+    }
+
+    /// For every `ReadModifyWrite`/`CompareExchange` `(atom_info,
+    /// interim_val)` pair, synthesize a Kani harness that interleaves its
+    /// read half and write half as two threads and hand it to Kani/CBMC.
+    /// Findings whose harness CBMC refutes (no counterexample interleaving
+    /// exists) are over-approximation noise and are dropped; a harness
+    /// with a counterexample is reported as `"Confirmed"`, and one Kani
+    /// can't run or classify (no toolchain installed, no verdict) is kept
+    /// as `"Unconfirmed"` rather than silently dropped, since we can't
+    /// tell noise from a real bug in that case.
+    fn detect_rmw_via_model_checking(
+        &self,
+        atom_infos: &HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>,
+        callgraph: &CallGraph<'tcx>,
+    ) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for (info, interim_val) in atom_infos.values().flatten() {
+            if !matches!(info.atomic_operate, Some(AtomicInstructions::ReadModifyWrite) | Some(AtomicInstructions::CompareExchange)) {
+                continue;
+            }
+            let Some(instance_node) = callgraph.index_to_instance(info.caller_instance) else {
+                continue;
+            };
+            let body = self.tcx.instance_mir(instance_node.instance().def);
+            let harness = kani::HarnessSpec::new(info, interim_val, body).render();
+            let verdict = kani::verify(&harness);
+            if verdict == kani::Verdict::Refuted {
+                continue;
+            }
+            let confidence = if verdict == kani::Verdict::Confirmed { "Confirmed" } else { "Unconfirmed" };
+            let diagnosis = AtomicityViolationDiagnosis {
+                atomic: info.source_info.clone(),
+            };
+            let report_content = ReportContent::new(
+                "AtomicityViolation".to_owned(),
+                confidence.to_owned(),
+                diagnosis,
+                "Points-to and data-dependency analysis found state that looks associated with \
+                 this read-modify-write, but that pairing is an over-approximation; a bounded \
+                 model-checking harness interleaving its read half and write half either found a \
+                 counterexample (confirmed) or couldn't be run to completion (unconfirmed)."
+                    .to_owned(),
+            );
+            reports.push(Report::AtomicCorrelationViolation(report_content));
+        }
+        reports
+    }
+
+    /// Flag a `compare_exchange_weak` whose call site isn't on any loop:
+    /// weak CAS is allowed to fail spuriously even when the comparison
+    /// would have succeeded, so using it outside a retry loop can silently
+    /// drop an update that a plain `compare_exchange` would have applied.
+    fn detect_weak_cas_outside_loop<'i>(
+        infos: impl Iterator<Item = &'i AtomicInfo<'tcx>>,
+    ) -> Vec<Report>
+    where
+        'tcx: 'i,
+    {
+        let mut reports = Vec::new();
+        for info in infos {
+            if info.atomic_operate != Some(AtomicInstructions::CompareExchange) || info.in_retry_loop {
+                continue;
+            }
+            let diagnosis = AtomicityViolationDiagnosis {
+                atomic: info.source_info.clone(),
+            };
+            let report_content = ReportContent::new(
+                "WeakCompareExchangeOutsideLoop".to_owned(),
+                "Possibly".to_owned(),
+                diagnosis,
+                "compare_exchange_weak can fail spuriously even when the comparison would have \
+                 succeeded, and is only sound inside a loop that retries on Err; this call site \
+                 isn't on any loop, so a spurious failure here silently drops the update."
+                    .to_owned(),
+            );
+            reports.push(Report::AtomicCorrelationViolation(report_content));
+        }
+        reports
+    }
+
+    /// Flag a strong `compare_exchange` whose call site lies on a loop in
+    /// the caller's CFG (the same `is_block_in_cycle` loop-membership check
+    /// `AtomicCollector` already uses to confirm a weak CAS's retry loop):
+    /// the loop already retries on `Err`, so `compare_exchange_weak` would
+    /// avoid the strong form's own implicit loop LL/SC retry on a weakly-
+    /// ordered target for free, since a spurious failure just gets retried
+    /// by the surrounding loop the same as a genuine comparison failure.
+    /// Doesn't apply to `fetch_update` (`cas_form` is `None` there): its
+    /// stdlib implementation already retries via `compare_exchange_weak`
+    /// internally, so there's no strong/weak choice at this call site to
+    /// improve on.
+    fn detect_strong_cas_in_retry_loop<'i>(
+        &self,
+        infos: impl Iterator<Item = &'i AtomicInfo<'tcx>>,
+        callgraph: &CallGraph<'tcx>,
+    ) -> Vec<Report>
+    where
+        'tcx: 'i,
+    {
+        let mut reports = Vec::new();
+        for info in infos {
+            if info.cas_form != Some(CasForm::Strong) {
+                continue;
+            }
+            let Some(instance_node) = callgraph.index_to_instance(info.caller_instance) else {
+                continue;
+            };
+            let body = self.tcx.instance_mir(instance_node.instance().def);
+            let Some(block) = block_for_span(body, info.span) else {
+                continue;
+            };
+            if !AtomicCollector::is_block_in_cycle(body, block) {
+                continue;
+            }
+            let diagnosis = AtomicityViolationDiagnosis {
+                atomic: info.source_info.clone(),
+            };
+            let report_content = ReportContent::new(
+                "StrongCompareExchangeInRetryLoop".to_owned(),
+                "Possibly".to_owned(),
+                diagnosis,
+                "This strong compare_exchange's call site lies on a retry loop, which already \
+                 retries on a failed comparison by looping back; compare_exchange_weak avoids \
+                 the implicit loop LL/SC retry the strong form adds on weakly-ordered targets, \
+                 since the surrounding loop already tolerates a spurious failure the same as a \
+                 genuine one."
+                    .to_owned(),
+            );
+            reports.push(Report::AtomicCorrelationViolation(report_content));
+        }
+        reports
+    }
+
+    /// Flag a publish/consume pair on the same atomic location where the
+    /// publishing `Store`/RMW doesn't reach `Release` or the consuming
+    /// `Load`/`CompareExchange` doesn't reach `Acquire`: without that pair,
+    /// there is no happens-before edge, so the consumer isn't guaranteed to
+    /// observe whatever the publisher wrote alongside the atomic.
+    ///
+    /// Only a publisher/consumer pair that can actually race is reported:
+    /// either they're in the same function with the publish preceding the
+    /// consume in program order (approximated the same way
+    /// `store_buffer_participants` does, by comparing `Span`s), or they're
+    /// in distinct, concurrently-reachable instances per `reachability`. A
+    /// single-threaded `x.store(1, Relaxed); let v = x.load(Relaxed);` with
+    /// no other thread touching `x` has no consumer that can ever miss the
+    /// publish, so it's not reported even though it shares a classified
+    /// location with the store.
+    fn detect_ordering_weakening(
+        atom_infos: &HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>,
+        reachability: &ConcurrencyReachability,
+    ) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for infos in atom_infos.values() {
+            let publishers = infos.iter().filter(|(info, _)| {
+                matches!(info.atomic_operate, Some(AtomicInstructions::Store) | Some(AtomicInstructions::ReadModifyWrite))
+                    && !info.atomic_value.is_empty()
+            });
+            let consumers: Vec<_> = infos
+                .iter()
+                .filter(|(info, _)| {
+                    matches!(info.atomic_operate, Some(AtomicInstructions::Load) | Some(AtomicInstructions::CompareExchange))
+                })
+                .collect();
+
+            for (publisher, _) in publishers {
+                let publish_ord = publisher.ordering.first().copied().unwrap_or_default();
+                for (consumer, _) in &consumers {
+                    let same_function_in_order =
+                        publisher.caller_instance == consumer.caller_instance && publisher.span.lo() < consumer.span.lo();
+                    let distinct_concurrent_instances = publisher.caller_instance != consumer.caller_instance
+                        && reachability.is_feasible(consumer.caller_instance, publisher.caller_instance);
+                    if !(same_function_in_order || distinct_concurrent_instances) {
+                        continue;
+                    }
+                    let consume_ord = consumer.ordering.first().copied().unwrap_or_default();
+                    if publish_ord.is_at_least(AtomicOrd::Release) && consume_ord.is_at_least(AtomicOrd::Acquire) {
+                        continue;
+                    }
+                    let diagnosis = AtomicityViolationDiagnosis {
+                        atomic: consumer.source_info.clone(),
+                    };
+                    let report_content = ReportContent::new(
+                        "AtomicOrderingWeakening".to_owned(),
+                        "Possibly".to_owned(),
+                        diagnosis,
+                        format!(
+                            "This access observes a value published by a `{:?}`-ordered atomic write at {}, but the publish/consume pair here is `{:?}`/`{:?}`; without at least Release/Acquire there is no happens-before edge, so the data written alongside the atomic isn't guaranteed visible.",
+                            publish_ord, publisher.source_info, publish_ord, consume_ord,
+                        ),
+                    );
+                    reports.push(Report::AtomicCorrelationViolation(report_content));
+                }
+            }
+        }
+        reports
+    }
+
+    /// Find every `AtomicInfo` taking part in the classic store-buffer/IRIW
+    /// shape: a store to one atomic variable followed, in program order
+    /// within the same function, by a load of a *different* atomic
+    /// variable, with a symmetric store-then-load pair on the swapped
+    /// variables somewhere else. Per-variable Release/Acquire pairing can
+    /// only order each variable against itself; ruling out both loads
+    /// missing the other side's write needs a single global total order
+    /// across all four operations, i.e. SeqCst.
+    ///
+    /// "Program order within the same function" is approximated by
+    /// comparing each operation's own `Span` (lower byte position means
+    /// earlier in the source), not a real control-flow-aware
+    /// happens-before analysis -- this crate's `analysis::controldep`/
+    /// `analysis::postdom` reason about branches, not statement order
+    /// along a single path. Good enough to flag the shape for review, not
+    /// a soundness proof.
+    /// `reachability` lets this recognize the classic two-thread shape (one
+    /// thread stores X then loads Y, a concurrently-running thread stores Y
+    /// then loads X) in addition to the single-function case: a store and a
+    /// load in two different, concurrently-reachable instances have no
+    /// program order between them to compare, so any such pair counts as a
+    /// candidate "store-then-load" edge, same as an in-order pair within one
+    /// function.
+    fn store_buffer_participants(atom_infos: &HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>, reachability: &ConcurrencyReachability) -> HashSet<AtomicInfo<'tcx>> {
+        // Every `(store variable, load variable, store, load)` where the
+        // store precedes the load in program order (same function), or the
+        // two run in distinct, concurrently-reachable instances (different
+        // threads, so no program order applies).
+        let mut store_then_load: Vec<(&str, &str, &AtomicInfo<'tcx>, &AtomicInfo<'tcx>)> = Vec::new();
+        for (store_var, store_infos) in atom_infos {
+            let stores = store_infos
+                .iter()
+                .filter(|(info, _)| matches!(info.atomic_operate, Some(AtomicInstructions::Store) | Some(AtomicInstructions::ReadModifyWrite)));
+            for (load_var, load_infos) in atom_infos {
+                if load_var == store_var {
+                    continue;
+                }
+                let loads = load_infos
+                    .iter()
+                    .filter(|(info, _)| matches!(info.atomic_operate, Some(AtomicInstructions::Load) | Some(AtomicInstructions::CompareExchange)));
+                for (store, _) in stores.clone() {
+                    for (load, _) in loads.clone() {
+                        let same_function_in_order = store.caller_instance == load.caller_instance && store.span.lo() < load.span.lo();
+                        let distinct_concurrent_instances =
+                            store.caller_instance != load.caller_instance && reachability.is_feasible(load.caller_instance, store.caller_instance);
+                        if same_function_in_order || distinct_concurrent_instances {
+                            store_then_load.push((store_var.as_str(), load_var.as_str(), store, load));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut participants = HashSet::new();
+        for (store_var, load_var, store, load) in &store_then_load {
+            let has_symmetric_pair = store_then_load
+                .iter()
+                .any(|(other_store_var, other_load_var, _, _)| other_store_var == load_var && other_load_var == store_var);
+            if has_symmetric_pair {
+                participants.insert((**store).clone());
+                participants.insert((**load).clone());
+            }
+        }
+        participants
+    }
+
+    /// Flag a classic ABA hazard on an `AtomicPtr` CAS-retry loop: the call
+    /// site compares a loaded pointer with `compare_exchange[_weak]`, and
+    /// somewhere in the same function that same pointer is also
+    /// dereferenced or handed to `Box::from_raw`/`dealloc`. If the backing
+    /// allocation is freed and a new one reused at the same address before
+    /// the retry's CAS fires, a raw pointer comparison can't tell the two
+    /// allocations apart and the CAS spuriously succeeds against the wrong
+    /// object.
+    ///
+    /// This only checks that the hazard's *shape* is present in the
+    /// function -- a dereference/free call site alongside a retry-loop CAS
+    /// on the same pointer, corroborated by the points-to map showing that
+    /// pointer can reach a freshly constructed allocation -- rather than
+    /// proving the free happens strictly between the load and the CAS:
+    /// that would need an intraprocedural must-happen-before dataflow this
+    /// crate doesn't have (`analysis::datadep` only tracks def/use, not
+    /// temporal ordering across arbitrary statements). Good enough to flag
+    /// the pattern for review, not a soundness proof.
+    fn detect_aba_hazard(
+        &self,
+        atomptr_infos: &[Vec<(AtomicInfo<'tcx>, Vec<Local>)>],
+        callgraph: &CallGraph<'tcx>,
+        alias_analysis: &AliasAnalysis<'tcx>,
+    ) -> Vec<Report> {
+        let reuse_site = Regex::new(r"^(.*::)?(Box::<.*>::from_raw|alloc::alloc::dealloc|__rust_dealloc)").unwrap();
+        let mut reports = Vec::new();
+        for (info, _) in atomptr_infos.iter().flatten() {
+            if info.atomic_operate != Some(AtomicInstructions::CompareExchange) || !info.is_conditional_store || !info.in_retry_loop {
+                continue;
+            }
+            let Some(expected) = info.expected_value else {
+                continue;
+            };
+            let Some(instance_node) = callgraph.index_to_instance(info.caller_instance) else {
+                continue;
+            };
+            let instance = instance_node.instance();
+            let body = self.tcx.instance_mir(instance.def);
+
+            let dereferenced = body
+                .basic_blocks
+                .iter()
+                .any(|block| block.statements.iter().any(|stmt| statement_derefs_local(stmt, expected.local)));
+            let freed_or_reused = body.basic_blocks.iter().any(|block| {
+                let TerminatorKind::Call { func, args, .. } = &block.terminator().kind else {
+                    return false;
+                };
+                let TyKind::FnDef(def_id, substs) = func.ty(body, self.tcx).kind() else {
+                    return false;
+                };
+                reuse_site.is_match(&self.tcx.def_path_str_with_substs(*def_id, substs))
+                    && args.iter().any(|arg| arg.place().is_some_and(|place| place.local == expected.local))
+            });
+            if !dereferenced && !freed_or_reused {
+                continue;
+            }
+
+            let points_to_map = alias_analysis.get_or_insert_pts(instance.def_id(), body);
+            let node = ConstraintNode::Place(Place::from(expected.local).as_ref());
+            let can_reach_fresh_alloc = points_to_map
+                .get(&node)
+                .map(|pts| pts.iter().any(|pointee| matches!(pointee, ConstraintNode::Construct(_) | ConstraintNode::Alloc(_))))
+                .unwrap_or(false);
+            if !can_reach_fresh_alloc {
+                continue;
+            }
+
+            let diagnosis = AtomicityViolationDiagnosis {
+                atomic: info.source_info.clone(),
+            };
+            let report_content = ReportContent::new(
+                "AbaHazard".to_owned(),
+                "Possibly".to_owned(),
+                diagnosis,
+                "This compare_exchange retries against a pointer that is also dereferenced or freed/reused \
+                 (Box::from_raw/dealloc) elsewhere in the same function, and the points-to analysis shows it \
+                 can reach a freshly constructed allocation; if the old allocation is freed and a new one \
+                 reused at the same address before the retry fires, the comparison can't detect the swap. \
+                 Consider a tagged/versioned pointer or a hazard-pointer scheme instead of comparing the raw \
+                 pointer alone."
+                    .to_owned(),
+            );
+            reports.push(Report::AtomicCorrelationViolation(report_content));
+        }
+        reports
+    }
+
+    /// Flag a `Relaxed` load that can observe a `SeqCst`/`Release` write
+    /// from a different, concurrently-reachable thread (see
+    /// `ConcurrencyReachability`) with no recognized synchronizes-with edge
+    /// between them. Two edges are recognized, both approximated
+    /// structurally rather than through a real happens-before model:
+    /// - a `.join()` call (`JoinHandle::<T>::join`) anywhere in the load's
+    ///   own function that control-flow-dominates the load's block -- once
+    ///   the writer's thread has been joined, every one of its writes is
+    ///   visible regardless of the load's own ordering;
+    /// - an earlier `Acquire`/`AcqRel`/`SeqCst` load on the *same* atomic,
+    ///   dominating this load in the same function -- that earlier load
+    ///   already synchronized-with the write, and this thread's own program
+    ///   order carries that forward to the later, weaker load.
+    ///
+    /// A same-function write is never flagged: sequenced-before within one
+    /// thread already guarantees the load sees it, with no ordering needed.
+    /// Doesn't model "mutex release" as a third synchronizes-with edge (the
+    /// request's third kind) -- recognizing an arbitrary `MutexGuard` drop
+    /// as synchronizing one specific atomic would need alias analysis this
+    /// pass has no other reason to build; scoped down to the two edges
+    /// above.
+    fn detect_stale_relaxed_read(
+        &self,
+        atom_infos: &HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>,
+        reachability: &ConcurrencyReachability,
+        callgraph: &CallGraph<'tcx>,
+    ) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for infos in atom_infos.values() {
+            let writes: Vec<&AtomicInfo<'tcx>> = infos
+                .iter()
+                .map(|(info, _)| info)
+                .filter(|info| {
+                    matches!(info.atomic_operate, Some(AtomicInstructions::Store) | Some(AtomicInstructions::ReadModifyWrite))
+                        && info.ordering.first().is_some_and(|ord| matches!(ord, AtomicOrd::SeqCst | AtomicOrd::Release))
+                })
+                .collect();
+            let reads: Vec<&AtomicInfo<'tcx>> = infos
+                .iter()
+                .map(|(info, _)| info)
+                .filter(|info| {
+                    matches!(info.atomic_operate, Some(AtomicInstructions::Load)) && info.ordering.first() == Some(&AtomicOrd::Relaxed)
+                })
+                .collect();
+
+            for read in &reads {
+                let Some(read_inst) = callgraph.index_to_instance(read.caller_instance) else {
+                    continue;
+                };
+                let read_body = self.tcx.instance_mir(read_inst.instance().def);
+                let Some(read_block) = block_for_span(read_body, read.span) else {
+                    continue;
+                };
+
+                let dominators = read_body.basic_blocks.dominators();
+                let synced_by_earlier_acquire = infos.iter().map(|(info, _)| info).any(|other| {
+                    other.caller_instance == read.caller_instance
+                        && matches!(other.atomic_operate, Some(AtomicInstructions::Load))
+                        && other.ordering.first().is_some_and(|ord| ord.is_at_least(AtomicOrd::Acquire))
+                        && block_for_span(read_body, other.span).is_some_and(|other_block| {
+                            dominators.dominates(other_block, read_block) && (other_block != read_block || other.span.lo() < read.span.lo())
+                        })
+                });
+                if synced_by_earlier_acquire || Self::has_join_before(self.tcx, read_body, read_block) {
+                    continue;
+                }
+
+                for write in &writes {
+                    if write.caller_instance == read.caller_instance || !reachability.is_feasible(read.caller_instance, write.caller_instance) {
+                        continue;
+                    }
+                    let diagnosis = AtomicityViolationDiagnosis {
+                        atomic: read.source_info.clone(),
+                    };
+                    let report_content = ReportContent::new(
+                        "StaleRelaxedRead".to_owned(),
+                        "Possibly".to_owned(),
+                        diagnosis,
+                        format!(
+                            "This Relaxed load can observe a write made with {:?} ordering from a \
+                             concurrently-reachable thread, but nothing in this function establishes a \
+                             synchronizes-with edge to it (no dominating `.join()`, and no earlier, stronger \
+                             load on the same atomic) -- the value it reads may be stale or arrive out of \
+                             order relative to that thread's other writes.",
+                            write.ordering.first().copied().unwrap_or(AtomicOrd::SeqCst)
+                        ),
+                    );
+                    reports.push(Report::AtomicCorrelationViolation(report_content));
+                    break;
+                }
+            }
+        }
         reports
     }
+
+    /// Whether `body` has any `JoinHandle::<T>::join` call dominating
+    /// `block` in `body`'s own control-flow graph -- the synchronizes-with
+    /// edge `detect_stale_relaxed_read` recognizes. Found the same way
+    /// `ConcurrencyReachability::thread_roots`/`FenceInfo::collect` find
+    /// their own call sites: matching `def_path_str_with_substs` against a
+    /// path suffix, here `::join` rather than anchoring on a crate prefix,
+    /// since `JoinHandle::join`'s receiver type is generic over the
+    /// spawned closure's return type and isn't worth resolving just to
+    /// confirm the path's root.
+    fn has_join_before(tcx: TyCtxt<'tcx>, body: &Body<'tcx>, block: BasicBlock) -> bool {
+        let dominators = body.basic_blocks.dominators();
+        for (candidate, data) in body.basic_blocks.iter_enumerated() {
+            let TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+                continue;
+            };
+            let TyKind::FnDef(def_id, substs) = func.ty(body, tcx).kind() else {
+                continue;
+            };
+            if tcx.def_path_str_with_substs(*def_id, substs).ends_with("::join") && dominators.dominates(candidate, block) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Flag a `fence`/`compiler_fence` call whose ordering adds nothing
+    /// over what the atomic ops in its own function already carry on their
+    /// own: if every atomic access in that function already uses `SeqCst`
+    /// (the strongest ordering there is), stacking an explicit fence on
+    /// top can't strengthen anything further -- it's pure overhead.
+    /// Doesn't attempt the inverse (a fence covering ops that are
+    /// individually weaker is doing real work, even once some other op in
+    /// the same function happens to already be strong enough) -- whether
+    /// those weaker ops actually need the fence's specific direction
+    /// (acquire/release) is exactly what `fence_covers` below already
+    /// exists to judge per-op, not per-fence.
+    fn detect_redundant_fence(&self, atom_infos: &HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>>, fences: &[FenceInfo]) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for fence in fences {
+            if fence.ordering != AtomicOrd::SeqCst {
+                continue;
+            }
+            let same_fn_atomics: Vec<&AtomicInfo<'tcx>> = atom_infos
+                .values()
+                .flatten()
+                .map(|(info, _)| info)
+                .filter(|info| info.caller_instance == fence.caller_instance)
+                .collect();
+            if same_fn_atomics.is_empty() || !same_fn_atomics.iter().all(|info| info.ordering.iter().all(|ord| *ord == AtomicOrd::SeqCst)) {
+                continue;
+            }
+            let diagnosis = AtomicityViolationDiagnosis {
+                atomic: self.tcx.sess.source_map().span_to_diagnostic_string(fence.span),
+            };
+            let report_content = ReportContent::new(
+                "RedundantSeqCstFence".to_owned(),
+                "Possibly".to_owned(),
+                diagnosis,
+                "Every atomic operation in this function already uses SeqCst, the strongest ordering \
+                 there is, so this fence can't add any further guarantee over what those operations \
+                 already carry on their own -- it's pure overhead."
+                    .to_owned(),
+            );
+            reports.push(Report::AtomicCorrelationViolation(report_content));
+        }
+        reports
+    }
+
+    /// Flag a user-defined `Clone` impl that constructs a brand-new
+    /// `Atomic*` (`AtomicUsize::new(..)`, etc.) rather than sharing the one
+    /// it's cloning from -- each clone then silently gets its own
+    /// independent counter/flag instead of observing the same atomic. This
+    /// is the anti-pattern the `futures-concurrency` fix that motivated
+    /// this check avoided by sharing atomics behind an `Arc` instead of
+    /// `#[derive(Clone)]`-ing (or hand-implementing `Clone` on) the struct
+    /// that holds them. Found the same way `FenceInfo::collect` finds
+    /// `fence`/`compiler_fence` call sites: matching `def_path_str_with_substs`
+    /// against an `Atomic*::new` pattern, scoped to call sites inside a
+    /// function whose own def path ends in `::clone` (a real `Clone::clone`
+    /// impl body, not merely a function named "clone").
+    fn detect_atomic_cloned_by_value(&self, callgraph: &CallGraph<'tcx>) -> Vec<Report> {
+        let re = Regex::new(r"^(std|core)::sync::atomic::Atomic[A-Za-z0-9]*::new$").unwrap();
+        let mut reports = Vec::new();
+        for (index, _) in callgraph.graph.node_references() {
+            let inst = match callgraph.index_to_instance(index).unwrap() {
+                CallGraphNode::WithBody(instance) => instance,
+                CallGraphNode::WithoutBody(_) => continue,
+            };
+            if !self.tcx.def_path_str(inst.def_id()).ends_with("::clone") {
+                continue;
+            }
+            let body = self.tcx.instance_mir(inst.def);
+            for data in body.basic_blocks.iter() {
+                let TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+                    continue;
+                };
+                let TyKind::FnDef(def_id, substs) = func.ty(body, self.tcx).kind() else {
+                    continue;
+                };
+                if re.find(&self.tcx.def_path_str_with_substs(*def_id, substs)).is_none() {
+                    continue;
+                }
+                let diagnosis = AtomicityViolationDiagnosis {
+                    atomic: self.tcx.sess.source_map().span_to_diagnostic_string(data.terminator().source_info.span),
+                };
+                let report_content = ReportContent::new(
+                    "AtomicClonedByValue".to_owned(),
+                    "Possibly".to_owned(),
+                    diagnosis,
+                    "This Clone impl constructs a brand-new atomic instead of sharing the one it's \
+                     cloning from, so every clone silently gets its own independent counter/flag rather \
+                     than observing the same state -- share the atomic behind an Arc instead of \
+                     deriving/implementing Clone on the type that owns it."
+                        .to_owned(),
+                );
+                reports.push(Report::AtomicCorrelationViolation(report_content));
+            }
+        }
+        reports
+    }
+
+    /// Whether a standalone fence already supplies the missing-direction
+    /// ordering for `atomic`, so a "too weak" report wouldn't also double-flag
+    /// the common "relaxed atomic + explicit fence" idiom. A `Store`/
+    /// `ReadModifyWrite` needs a `Release`-or-stronger fence that *dominates*
+    /// its call site (runs on every path reaching it, so the fence's release
+    /// semantics are guaranteed to have already taken effect); a `Load` needs
+    /// an `Acquire`-or-stronger fence that *post-dominates* it (runs on every
+    /// path leaving it); a `CompareExchange` is simultaneously a read and a
+    /// conditional write, so it needs both. Same-block ordering (fence and
+    /// atomic op sharing one `BasicBlock`) falls back to comparing `Span`
+    /// byte positions, the same approximation `store_buffer_participants`
+    /// uses for program order, since dominance alone can't distinguish "fence
+    /// before the atomic" from "fence after it" within a single block.
+    fn fence_covers(&self, atomic: &AtomicInfo<'tcx>, fences: &[FenceInfo], callgraph: &CallGraph<'tcx>) -> bool {
+        let Some(instance_node) = callgraph.index_to_instance(atomic.caller_instance) else {
+            return false;
+        };
+        let body = self.tcx.instance_mir(instance_node.instance().def);
+        let Some(atomic_block) = block_for_span(body, atomic.span) else {
+            return false;
+        };
+        let same_fn_fences: Vec<&FenceInfo> = fences.iter().filter(|f| f.caller_instance == atomic.caller_instance).collect();
+        if same_fn_fences.is_empty() {
+            return false;
+        }
+
+        let dominators = body.basic_blocks.dominators();
+        let dominated_by_release = same_fn_fences.iter().any(|f| {
+            f.ordering.is_at_least(AtomicOrd::Release)
+                && dominators.dominates(f.block, atomic_block)
+                && (f.block != atomic_block || f.span.lo() < atomic.span.lo())
+        });
+
+        let pdt = postdom::post_dominators(&body.basic_blocks);
+        let post_dominated_by_acquire = same_fn_fences.iter().any(|f| {
+            f.ordering.is_at_least(AtomicOrd::Acquire)
+                && pdt.is_post_dominated_by(atomic_block, f.block)
+                && (f.block != atomic_block || f.span.lo() > atomic.span.lo())
+        });
+
+        match atomic.atomic_operate {
+            Some(AtomicInstructions::Store) | Some(AtomicInstructions::ReadModifyWrite) => dominated_by_release,
+            Some(AtomicInstructions::Load) => post_dominated_by_acquire,
+            Some(AtomicInstructions::CompareExchange) => dominated_by_release && post_dominated_by_acquire,
+            None => false,
+        }
+    }
+}
+
+/// Whether `stmt` assigns a place that dereferences `local` -- used by
+/// `AtomicityViolationDetector::detect_aba_hazard` as a cheap, purely
+/// structural stand-in for "is this pointer read through" (no points-to or
+/// escape analysis of its own).
+fn statement_derefs_local(stmt: &Statement<'_>, local: Local) -> bool {
+    let StatementKind::Assign(assign) = &stmt.kind else {
+        return false;
+    };
+    let (_, rvalue) = &**assign;
+    let derefs = |place: &Place<'_>| {
+        place.local == local && place.projection.iter().any(|elem| matches!(elem, ProjectionElem::Deref))
+    };
+    match rvalue {
+        Rvalue::Use(Operand::Copy(place)) | Rvalue::Use(Operand::Move(place)) => derefs(place),
+        Rvalue::Ref(_, _, place) | Rvalue::AddressOf(_, place) => derefs(place),
+        _ => false,
+    }
 }
 
 /// CallSite Locations from source to target
@@ -0,0 +1,290 @@
+//! Detect the tokio `broadcast` "slow receiver" hazard: among several
+//! `Receiver`s subscribed from the same `Sender` (sharing one bounded ring
+//! buffer), a receiver whose own consumption is structurally slower than
+//! its siblings' (gated behind a lock, or draining inside a bigger loop)
+//! can let the buffer fill, dropping messages with a `Lagged` error its own
+//! `recv()` call site doesn't even check for.
+//!
+//! Scoped to receivers subscribed within a single function, the same way
+//! `detector::chan::ChannelDeadlockDetector::detect_in_caller` is scoped
+//! before its own `detect_cross_thread` extension -- tracing a cloned
+//! `Sender` (and the receivers produced from it) across separate spawned
+//! closures would need the same capture-mapping machinery
+//! `detect_cross_thread` built for plain channels, which is a natural
+//! follow-up but adds real complexity this pass doesn't pay for its own
+//! single-function case.
+extern crate rustc_hash;
+extern crate rustc_middle;
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::visit::IntoNodeReferences;
+use petgraph::Direction::Incoming;
+use rustc_middle::mir::{BasicBlock, Body, Local, Location, Rvalue, StatementKind, TerminatorKind};
+use rustc_middle::ty::TyCtxt;
+
+use crate::analysis::callgraph::{CallGraph, InstanceId};
+use crate::detector::chan::report::ChannelDeadlockDiagnosis;
+use crate::detector::report::{Report, ReportContent};
+use crate::interest::concurrency::broadcast::BroadcastApi;
+
+pub struct SlowReceiverDetector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> SlowReceiverDetector<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    /// Every `BroadcastApi` call site, grouped by the function it occurs in
+    /// -- same shape as `ChannelDeadlockDetector::collect_chan_sites`.
+    fn collect_broadcast_sites(&self, callgraph: &CallGraph<'tcx>) -> HashMap<InstanceId, Vec<(BroadcastApi, Location, Local)>> {
+        let broadcast_callees: HashMap<InstanceId, BroadcastApi> = callgraph
+            .graph
+            .node_references()
+            .filter_map(|(id, node)| BroadcastApi::from_def_id(node.instance().def_id(), self.tcx).map(|api| (id, api)))
+            .collect();
+
+        let mut sites_by_caller: HashMap<InstanceId, Vec<(BroadcastApi, Location, Local)>> = HashMap::new();
+        for (&callee, &api) in &broadcast_callees {
+            let callers: Vec<InstanceId> = callgraph.graph.neighbors_directed(callee, Incoming).collect();
+            for caller in callers {
+                let Some(callsites) = callgraph.callsites(caller, callee) else {
+                    continue;
+                };
+                let inst = callgraph.index_to_instance(caller).unwrap();
+                let body = self.tcx.instance_mir(inst.instance().def);
+                for callsite in callsites {
+                    let Some(location) = callsite.location() else { continue };
+                    if let TerminatorKind::Call { args, destination, .. } = &body[location.block].terminator().kind {
+                        let local = match api {
+                            BroadcastApi::Create | BroadcastApi::Subscribe => destination.local,
+                            BroadcastApi::Send | BroadcastApi::Recv => match args.first().and_then(|arg| arg.place()) {
+                                Some(place) => place.local,
+                                None => continue,
+                            },
+                        };
+                        sites_by_caller.entry(caller).or_default().push((api, location, local));
+                    }
+                }
+            }
+        }
+        sites_by_caller
+    }
+
+    /// Same move-chain approximation as
+    /// `ChannelDeadlockDetector::traces_to` -- not a full alias analysis, so
+    /// a `Sender`/`Receiver` threaded through a struct field or an `Arc`
+    /// clone won't be recognized as the same channel.
+    fn traces_to(body: &Body<'tcx>, mut local: Local, create_local: Local) -> bool {
+        let mut seen = HashSet::new();
+        while local != create_local && seen.insert(local) {
+            let before = local;
+            for block in body.basic_blocks.indices() {
+                for statement in &body[block].statements {
+                    if let StatementKind::Assign(assign) = &statement.kind {
+                        let (place, rvalue) = &**assign;
+                        if place.local == local && place.projection.is_empty() {
+                            let src = match rvalue {
+                                Rvalue::Use(operand) => operand.place(),
+                                Rvalue::Ref(_, _, src) => Some(*src),
+                                _ => None,
+                            };
+                            if let Some(src) = src {
+                                local = src.local;
+                            }
+                        }
+                    }
+                }
+            }
+            if local == before {
+                break;
+            }
+        }
+        local == create_local
+    }
+
+    /// Whether `block` is dominated by a `Mutex`/`RwLock` acquisition call
+    /// in the same function -- the "gated behind a lock" half of the
+    /// slow-receiver heuristic. Matched by path suffix the same way
+    /// `ChannelDeadlockDetector::escapes_into_spawn` matches `spawn`,
+    /// rather than resolving the guard's concrete type: a `lock`/`read`/
+    /// `write` call on anything reads as "this receiver only gets to
+    /// consume after acquiring some lock first".
+    fn gated_by_lock(tcx: TyCtxt<'tcx>, body: &Body<'tcx>, block: BasicBlock) -> bool {
+        let dominators = body.basic_blocks.dominators();
+        for (candidate, data) in body.basic_blocks.iter_enumerated() {
+            let TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+                continue;
+            };
+            let Some((def_id, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let path = tcx.def_path_str(def_id);
+            let is_lock_call = (path.contains("Mutex") || path.contains("RwLock")) && (path.ends_with("::lock") || path.ends_with("::read") || path.ends_with("::write"));
+            if is_lock_call && candidate != block && dominators.dominates(candidate, block) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Number of basic blocks in the smallest natural loop (see
+    /// `interest::concurrency::atomic::AtomicCollector::is_block_in_cycle`)
+    /// containing `block`, or `0` if `block` isn't on any loop -- the
+    /// "longer loop than its siblings" half of the heuristic, compared
+    /// between sibling receivers rather than against any absolute
+    /// threshold.
+    fn enclosing_loop_size(body: &Body<'tcx>, block: BasicBlock) -> usize {
+        let dominators = body.basic_blocks.dominators();
+        let mut smallest = 0usize;
+        for (latch, data) in body.basic_blocks.iter_enumerated() {
+            for header in data.terminator().successors() {
+                if !dominators.dominates(header, latch) {
+                    continue;
+                }
+                let mut seen = HashSet::new();
+                seen.insert(header);
+                let mut worklist = vec![latch];
+                while let Some(bb) = worklist.pop() {
+                    if !seen.insert(bb) {
+                        continue;
+                    }
+                    for pred in body.basic_blocks.predecessors()[bb].iter() {
+                        worklist.push(*pred);
+                    }
+                }
+                if seen.contains(&block) && (smallest == 0 || seen.len() < smallest) {
+                    smallest = seen.len();
+                }
+            }
+        }
+        smallest
+    }
+
+    /// Whether `recv_loc`'s own `Result` is ever pattern-matched anywhere
+    /// in `body` -- a `Discriminant` read off it, or off a local it's
+    /// moved into -- as opposed to being driven straight into
+    /// `.unwrap()`/`.expect()`, which never distinguishes `Lagged` from a
+    /// clean message. Whole-function rather than control-flow-scoped to
+    /// just the path leaving `recv_loc`, the same coarse "does this value
+    /// get matched anywhere" approximation
+    /// `AtomicOrd::from_local_assignment` already uses for a different
+    /// value.
+    fn handles_lag_or_closed(body: &Body<'tcx>, recv_loc: Location) -> bool {
+        let TerminatorKind::Call { destination, .. } = &body[recv_loc.block].terminator().kind else {
+            return false;
+        };
+        let result_local = destination.local;
+        for block in body.basic_blocks.indices() {
+            for statement in &body[block].statements {
+                let StatementKind::Assign(assign) = &statement.kind else { continue };
+                let (_, rvalue) = &**assign;
+                if let Rvalue::Discriminant(place) = rvalue {
+                    if Self::traces_to(body, place.local, result_local) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    pub fn detect(&mut self, callgraph: &CallGraph<'tcx>) -> Vec<Report> {
+        let sites_by_caller = self.collect_broadcast_sites(callgraph);
+        let mut reports = Vec::new();
+        for (&caller, sites) in &sites_by_caller {
+            reports.extend(self.detect_in_caller(callgraph, caller, sites));
+        }
+        reports
+    }
+
+    fn detect_in_caller(&self, callgraph: &CallGraph<'tcx>, caller: InstanceId, sites: &[(BroadcastApi, Location, Local)]) -> Vec<Report> {
+        let mut reports = Vec::new();
+        let inst = callgraph.index_to_instance(caller).unwrap();
+        let body = self.tcx.instance_mir(inst.instance().def);
+
+        let creates: Vec<Local> = sites.iter().filter(|(api, ..)| *api == BroadcastApi::Create).map(|(_, _, l)| *l).collect();
+        let subscribes: Vec<Local> = sites.iter().filter(|(api, ..)| *api == BroadcastApi::Subscribe).map(|(_, _, l)| *l).collect();
+        let recvs: Vec<(Location, Local)> = sites.iter().filter(|(api, ..)| *api == BroadcastApi::Recv).map(|(_, loc, l)| (*loc, *l)).collect();
+
+        // Every receiver this function can see: the `channel()` call's own
+        // destructured local (see `traces_to`'s known imprecision on tuple
+        // destructuring -- it can't distinguish the `Sender` half from the
+        // `Receiver` half of the pair, the same simplification
+        // `ChanApi::Create` already accepts for `std::sync::mpsc`), plus
+        // one per `subscribe()` call.
+        let receivers: Vec<Local> = creates.iter().chain(subscribes.iter()).copied().collect();
+        if receivers.len() < 2 {
+            // No sibling to be structurally slower than.
+            return reports;
+        }
+
+        struct ReceiverProfile {
+            recv_loc: Location,
+            gated_by_lock: bool,
+            loop_size: usize,
+            handles_lag: bool,
+        }
+
+        let mut profiles = Vec::new();
+        for &receiver in &receivers {
+            for &(recv_loc, recv_local) in &recvs {
+                if !Self::traces_to(body, recv_local, receiver) {
+                    continue;
+                }
+                profiles.push(ReceiverProfile {
+                    recv_loc,
+                    gated_by_lock: Self::gated_by_lock(self.tcx, body, recv_loc.block),
+                    loop_size: Self::enclosing_loop_size(body, recv_loc.block),
+                    handles_lag: Self::handles_lag_or_closed(body, recv_loc),
+                });
+            }
+        }
+        if profiles.len() < 2 {
+            return reports;
+        }
+
+        let min_loop_size = profiles.iter().map(|p| p.loop_size).min().unwrap_or(0);
+        let any_unlocked = profiles.iter().any(|p| !p.gated_by_lock);
+
+        for profile in &profiles {
+            if profile.handles_lag {
+                continue;
+            }
+            let structurally_slower = (profile.gated_by_lock && any_unlocked) || profile.loop_size > min_loop_size;
+            if !structurally_slower {
+                continue;
+            }
+            let source_map = self.tcx.sess.source_map();
+            let recv_span = body[profile.recv_loc.block].terminator().source_info.span;
+            // `ChannelDeadlockDiagnosis` belongs to `std::sync::mpsc`
+            // channel deadlocks; reused here since its own defining file,
+            // `detector::chan::report`, doesn't exist in this tree to add
+            // a dedicated broadcast diagnosis type to. `recv` holds this
+            // receiver's own call site; `excluding_branch` is left empty
+            // since there's no competing branch here, just a slower
+            // sibling.
+            let diagnosis = ChannelDeadlockDiagnosis {
+                recv: source_map.span_to_diagnostic_string(recv_span),
+                excluding_branch: String::new(),
+            };
+            let report_content = ReportContent::new(
+                "BroadcastSlowReceiver".to_owned(),
+                "Possibly".to_owned(),
+                diagnosis,
+                format!(
+                    "This receiver consumes {} than at least one sibling subscribed to the same \
+                     broadcast channel, and its own recv() doesn't check for the Lagged/Closed \
+                     variants -- once it falls far enough behind, the ring buffer can overwrite \
+                     messages it hasn't read yet, which surfaces as a silently-ignored lag error \
+                     rather than a value.",
+                    if profile.gated_by_lock { "more slowly (gated behind a lock)" } else { "in a longer loop" }
+                ),
+            );
+            reports.push(Report::ChannelDeadlock(report_content));
+        }
+        reports
+    }
+}
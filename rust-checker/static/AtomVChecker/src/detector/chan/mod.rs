@@ -0,0 +1,675 @@
+//! Detect channel deadlocks / lost signals: a `Receiver::recv` that is
+//! control-dependent on a branch whose matching `Sender::send` only ever
+//! runs on the *other* arm (or not at all in this function) -- on the path
+//! that reaches the `recv`, nothing will ever wake it up.
+//! ```no_run
+//! if condition {
+//!     tx.send(v).unwrap();
+//! } else {
+//!     // no send on this arm
+//! }
+//! rx.recv().unwrap(); // fine if `condition` is always true, but recv
+//!                      // is reachable even when it isn't
+//! ```
+extern crate rustc_hash;
+extern crate rustc_middle;
+extern crate rayon;
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::visit::IntoNodeReferences;
+use petgraph::Direction::Incoming;
+use rayon::prelude::*;
+use regex::Regex;
+use rustc_data_structures::graph::dominators::Dominators;
+use rustc_middle::mir::{AggregateKind, BasicBlock, Body, Local, Location, Operand, ProjectionElem, Rvalue, StatementKind, TerminatorKind};
+use rustc_middle::ty::{Instance, TyCtxt, TyKind};
+
+pub mod report;
+
+use crate::analysis::callgraph::{CallGraph, CallGraphNode, InstanceId};
+use crate::analysis::controldep;
+use crate::detector::chan::report::ChannelDeadlockDiagnosis;
+use crate::detector::report::{Report, ReportContent};
+use crate::interest::concurrency::chan::ChanApi;
+
+/// A `thread::spawn`/`thread::Builder::spawn` call site: the spawning
+/// function, the spawned closure's own `InstanceId` (so its channel sites
+/// are looked up the same way any other caller's are), and -- for every
+/// upvar the closure captures -- the spawner-frame local moved into it,
+/// indexed by capture (field) position. Only a closure literal built
+/// directly in the `spawn` call's own argument position is matched; one
+/// threaded through another move first isn't followed, the same "plain
+/// move chain" scope `ChannelDeadlockDetector::traces_to` already has.
+struct SpawnSite {
+    spawner: InstanceId,
+    closure: InstanceId,
+    captures: Vec<Local>,
+}
+
+pub struct ChannelDeadlockDetector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> ChannelDeadlockDetector<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    /// Every `ChanApi` call site, grouped by the function it occurs in, as
+    /// `(api, callsite location, the `Sender`/`Receiver` local involved --
+    /// the destination for `Create`, the `self` argument for `Send`/`Recv`)`.
+    fn collect_chan_sites(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+    ) -> HashMap<InstanceId, Vec<(ChanApi, Location, Local)>> {
+        let chan_callees: HashMap<InstanceId, ChanApi> = callgraph
+            .graph
+            .node_references()
+            .filter_map(|(id, node)| {
+                ChanApi::from_def_id(node.instance().def_id(), self.tcx).map(|api| (id, api))
+            })
+            .collect();
+
+        let mut sites_by_caller: HashMap<InstanceId, Vec<(ChanApi, Location, Local)>> = HashMap::new();
+        for (&callee, &api) in &chan_callees {
+            let callers: Vec<InstanceId> = callgraph.graph.neighbors_directed(callee, Incoming).collect();
+            for caller in callers {
+                let Some(callsites) = callgraph.callsites(caller, callee) else {
+                    continue;
+                };
+                let inst = callgraph.index_to_instance(caller).unwrap();
+                let body = self.tcx.instance_mir(inst.instance().def);
+                for callsite in callsites {
+                    let Some(location) = callsite.location() else { continue };
+                    if let TerminatorKind::Call { args, destination, .. } = &body[location.block].terminator().kind {
+                        let local = match api {
+                            ChanApi::Create | ChanApi::CreateBounded => destination.local,
+                            ChanApi::Send | ChanApi::Recv => match args.first().and_then(|arg| arg.place()) {
+                                Some(place) => place.local,
+                                None => continue,
+                            },
+                        };
+                        sites_by_caller.entry(caller).or_default().push((api, location, local));
+                    }
+                }
+            }
+        }
+        sites_by_caller
+    }
+
+    /// Whether `local`'s value can be traced back to `create_local` through
+    /// a chain of plain moves (`_a = move _b`) and borrows (`_a = &_b`, the
+    /// shape a `&self` call's receiver argument takes, e.g. `rx.recv()`).
+    /// This is the common shape for
+    /// `let (tx, rx) = channel(); some_fn(tx)` -- not a full alias analysis,
+    /// so a `Sender`/`Receiver` threaded through a struct field or an
+    /// `Arc`/`Rc` clone won't be recognized as the same channel.
+    fn traces_to(body: &Body<'tcx>, mut local: Local, create_local: Local) -> bool {
+        // Each pass follows the links it meets in block order; passes repeat
+        // until one finds nothing new, so a chain whose moves appear
+        // source-first in program order is followed back too.
+        let mut seen = HashSet::new();
+        while local != create_local && seen.insert(local) {
+            let before = local;
+            for block in body.basic_blocks.indices() {
+                for statement in &body[block].statements {
+                    if let StatementKind::Assign(assign) = &statement.kind {
+                        let (place, rvalue) = &**assign;
+                        if place.local == local && place.projection.is_empty() {
+                            let src = match rvalue {
+                                Rvalue::Use(operand) => operand.place(),
+                                Rvalue::Ref(_, _, src) => Some(*src),
+                                _ => None,
+                            };
+                            if let Some(src) = src {
+                                local = src.local;
+                            }
+                        }
+                    }
+                }
+            }
+            if local == before {
+                break;
+            }
+        }
+        local == create_local
+    }
+
+    /// Whether `local` is moved into a `thread::spawn`/`tokio::spawn` call
+    /// anywhere in `body`. A sender moved into a spawned closure can still
+    /// send from there; since this detector doesn't follow into spawned
+    /// closures, treat the handle as having escaped and don't flag it.
+    fn escapes_into_spawn(&self, body: &Body<'tcx>, local: Local) -> bool {
+        for block in body.basic_blocks.indices() {
+            if let TerminatorKind::Call { func, args, .. } = &body[block].terminator().kind {
+                let Some((def_id, _)) = func.const_fn_def() else { continue };
+                let path = self.tcx.def_path_str(def_id);
+                if !(path.ends_with("thread::spawn") || path.ends_with("spawn")) {
+                    continue;
+                }
+                if args.iter().any(|arg| arg.place().is_some_and(|place| place.local == local)) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Each caller's control-flow is independent of every other's, so this
+    /// is driven by `detect` over a rayon work-stealing queue rather than a
+    /// sequential loop.
+    pub fn detect(&mut self, callgraph: &CallGraph<'tcx>) -> Vec<Report> {
+        let sites_by_caller = self.collect_chan_sites(callgraph);
+        let mut reports: Vec<Report> = sites_by_caller
+            .par_iter()
+            .flat_map_iter(|(caller, sites)| self.detect_in_caller(callgraph, *caller, sites))
+            .collect();
+        reports.extend(self.detect_cross_thread(callgraph, &sites_by_caller));
+        reports.extend(self.detect_backpressure_deadlock(callgraph, &sites_by_caller));
+        reports
+    }
+
+    fn detect_in_caller(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        caller: InstanceId,
+        sites: &[(ChanApi, Location, Local)],
+    ) -> Vec<Report> {
+        let mut reports = Vec::new();
+        {
+            let inst = callgraph.index_to_instance(caller).unwrap();
+            let body = self.tcx.instance_mir(inst.instance().def);
+            // Control-dependence: `parents[b]` is the set of branch blocks
+            // `b` can only be reached via a specific successor of.
+            let control_deps = controldep::control_deps(body.basic_blocks.clone());
+            // Forward dominance: which successor of a branch must run before
+            // a given block can.
+            let dominators = body.basic_blocks.dominators();
+
+            let creates: Vec<Local> = sites.iter().filter(|(api, ..)| matches!(api, ChanApi::Create | ChanApi::CreateBounded)).map(|(_, _, l)| *l).collect();
+            let sends: Vec<(Location, Local)> = sites.iter().filter(|(api, ..)| *api == ChanApi::Send).map(|(_, loc, l)| (*loc, *l)).collect();
+            let recvs: Vec<(Location, Local)> = sites.iter().filter(|(api, ..)| *api == ChanApi::Recv).map(|(_, loc, l)| (*loc, *l)).collect();
+
+            for &(recv_loc, recv_local) in &recvs {
+                let Some(&create_local) = creates.iter().find(|&&c| Self::traces_to(body, recv_local, c)) else {
+                    continue;
+                };
+                if self.escapes_into_spawn(body, create_local) {
+                    continue;
+                }
+                let matching_sends: Vec<Location> = sends
+                    .iter()
+                    .filter(|&&(_, send_local)| Self::traces_to(body, send_local, create_local))
+                    .map(|&(loc, _)| loc)
+                    .collect();
+
+                for &branch in &control_deps.banch_node {
+                    if !control_deps.parents[recv_loc.block].contains(&branch) {
+                        continue;
+                    }
+                    let successors: Vec<_> = body[branch].terminator().successors().collect();
+                    // Only the common two-way branch (if/else, or a match
+                    // with two arms) is modeled; wider switches are skipped
+                    // rather than guessed at.
+                    if successors.len() != 2 {
+                        continue;
+                    }
+                    let Some(&recv_side) = successors.iter().find(|&&s| dominators.dominates(s, recv_loc.block)) else {
+                        continue;
+                    };
+                    let Some(other_side) = successors.iter().find(|&&s| s != recv_side).copied() else {
+                        continue;
+                    };
+                    // A send anywhere between `recv_side` and the recv itself
+                    // would resolve this recv; if dominance puts a matching
+                    // send there, this arm is fine.
+                    //
+                    // Note this can't tell a send that unconditionally runs
+                    // on `recv_side` from one buried in a loop body that may
+                    // execute zero times -- dominance says "runs before",
+                    // not "always runs at least once" -- so a send inside
+                    // such a loop can suppress a real finding here.
+                    if matching_sends.iter().any(|send_loc| dominators.dominates(recv_side, send_loc.block)) {
+                        continue;
+                    }
+                    let send_only_on_other_arm = !matching_sends.is_empty()
+                        && matching_sends.iter().all(|send_loc| dominators.dominates(other_side, send_loc.block));
+                    if matching_sends.is_empty() || send_only_on_other_arm {
+                        let recv_span = body[recv_loc.block].terminator().source_info.span;
+                        let branch_span = body[branch].terminator().source_info.span;
+                        let source_map = self.tcx.sess.source_map();
+                        let diagnosis = ChannelDeadlockDiagnosis {
+                            recv: source_map.span_to_diagnostic_string(recv_span),
+                            excluding_branch: source_map.span_to_diagnostic_string(branch_span),
+                        };
+                        let report_content = ReportContent::new(
+                            "ChannelDeadlock".to_owned(),
+                            "Possibly".to_owned(),
+                            diagnosis,
+                            "This receive is control-dependent on a branch whose matching send \
+                             only happens on the other arm (or not in this function at all); on \
+                             the path that reaches this receive, nothing ever sends on the \
+                             channel, so it blocks forever."
+                                .to_owned(),
+                        );
+                        reports.push(Report::ChannelDeadlock(report_content));
+                    }
+                }
+            }
+        }
+        reports
+    }
+
+    /// Every `thread::spawn`-shaped call site reachable in the crate, found
+    /// the same way `ConcurrencyReachability::thread_roots` in
+    /// `detector::atomic` finds them: matching `def_path_str_with_substs`
+    /// on call terminators rather than anything callgraph-structural.
+    fn find_spawn_sites(&self, callgraph: &CallGraph<'tcx>) -> Vec<SpawnSite> {
+        let re = Regex::new(r"^(std::thread::spawn|std::thread::Builder::spawn)").unwrap();
+        let mut sites = Vec::new();
+        for (index, _) in callgraph.graph.node_references() {
+            let inst = match callgraph.index_to_instance(index).unwrap() {
+                CallGraphNode::WithBody(instance) => instance,
+                CallGraphNode::WithoutBody(_) => continue,
+            };
+            let body = self.tcx.instance_mir(inst.def);
+            for block in body.basic_blocks.iter() {
+                let TerminatorKind::Call { func, args, .. } = &block.terminator().kind else { continue };
+                let Some((def_id, substs)) = func.const_fn_def() else { continue };
+                if re.find(&self.tcx.def_path_str_with_substs(def_id, substs)).is_none() {
+                    continue;
+                }
+                let Some(closure_arg) = args.first() else { continue };
+                let TyKind::Closure(closure_def_id, closure_substs) = closure_arg.ty(body, self.tcx).kind() else { continue };
+                let closure_instance = Instance::new(*closure_def_id, closure_substs);
+                let Some(closure_id) = callgraph.instance_to_index(closure_instance) else { continue };
+                let Some(closure_place) = closure_arg.place() else { continue };
+                let captures = Self::closure_captures(body, closure_place.local);
+                sites.push(SpawnSite { spawner: index, closure: closure_id, captures });
+            }
+        }
+        sites
+    }
+
+    /// For the closure literal assigned to `closure_local` in `body`, the
+    /// spawner-frame local captured into each upvar field, in field order
+    /// -- e.g. `captures[0]` is what shows up as `_1.0` inside the
+    /// closure's own body (see `capture_field`, its inverse).
+    fn closure_captures(body: &Body<'tcx>, closure_local: Local) -> Vec<Local> {
+        for block in body.basic_blocks.indices() {
+            for statement in &body[block].statements {
+                let StatementKind::Assign(assign) = &statement.kind else { continue };
+                let (place, rvalue) = &**assign;
+                if place.local != closure_local || !place.projection.is_empty() {
+                    continue;
+                }
+                let Rvalue::Aggregate(kind, operands) = rvalue else { continue };
+                if !matches!(kind.as_ref(), AggregateKind::Closure(..)) {
+                    continue;
+                }
+                return operands
+                    .iter()
+                    .filter_map(|operand| match operand {
+                        Operand::Move(place) | Operand::Copy(place) => Some(place.local),
+                        Operand::Constant(_) => None,
+                    })
+                    .collect();
+            }
+        }
+        Vec::new()
+    }
+
+    /// The inverse of `closure_captures`: whether `local`, inside a
+    /// spawned closure's own body, is (after a chain of plain moves) a
+    /// direct field projection of the closure's environment (`_1`, the
+    /// first argument of a `FnOnce::call_once` body) -- i.e. a captured
+    /// upvar -- and if so, which field index.
+    fn capture_field(body: &Body<'tcx>, mut local: Local) -> Option<usize> {
+        loop {
+            let mut moved_from = None;
+            for block in body.basic_blocks.indices() {
+                for statement in &body[block].statements {
+                    let StatementKind::Assign(assign) = &statement.kind else { continue };
+                    let (place, rvalue) = &**assign;
+                    if place.local != local || !place.projection.is_empty() {
+                        continue;
+                    }
+                    let Rvalue::Use(operand) = rvalue else { continue };
+                    let Some(src) = operand.place() else { continue };
+                    if src.local.as_u32() == 1 {
+                        if let [ProjectionElem::Field(idx, _)] = src.as_ref().projection {
+                            return Some(idx.as_usize());
+                        }
+                    }
+                    moved_from = Some(src.local);
+                }
+            }
+            local = moved_from?;
+        }
+    }
+
+    /// The second test program this is aimed at is the textbook
+    /// cross-channel deadlock: a spawned thread blocks on its own `recv`
+    /// waiting for a `send` the spawning thread only reaches after the
+    /// spawning thread's *own* blocking `recv` -- which in turn waits on a
+    /// `send` the spawned thread only issues after its `recv` unblocks.
+    /// Neither side can break the cycle, since each side's next `send` is
+    /// sequenced after a `recv` that never returns.
+    ///
+    /// Modeled as a two-node wait-for graph (the spawning function, and
+    /// the spawned closure) rather than a full per-thread graph: channel
+    /// identity across the two frames is tracked via `find_spawn_sites`'s
+    /// capture mapping, and an edge "X depends on Y" is added whenever X
+    /// has a blocking op on a channel Y has the matching op on. A bounded
+    /// (`sync_channel`) channel's `send` is itself potentially blocking
+    /// (the rendezvous case), so it adds the symmetric edge a `recv` would,
+    /// not just the one-directional "recv needs send" edge an unbounded
+    /// channel's `send` would. A cycle (both edges present) is reported.
+    fn detect_cross_thread(&self, callgraph: &CallGraph<'tcx>, sites_by_caller: &HashMap<InstanceId, Vec<(ChanApi, Location, Local)>>) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for spawn in self.find_spawn_sites(callgraph) {
+            let (Some(spawner_sites), Some(closure_sites)) = (sites_by_caller.get(&spawn.spawner), sites_by_caller.get(&spawn.closure)) else {
+                continue;
+            };
+
+            let spawner_inst = callgraph.index_to_instance(spawn.spawner).unwrap().instance();
+            let spawner_body = self.tcx.instance_mir(spawner_inst.def);
+            let closure_inst = callgraph.index_to_instance(spawn.closure).unwrap().instance();
+            let closure_body = self.tcx.instance_mir(closure_inst.def);
+
+            let spawner_creates: Vec<Local> = spawner_sites
+                .iter()
+                .filter(|(api, ..)| matches!(api, ChanApi::Create | ChanApi::CreateBounded))
+                .map(|(_, _, l)| *l)
+                .collect();
+            let bounded_creates: HashSet<Local> = spawner_sites
+                .iter()
+                .filter(|(api, ..)| *api == ChanApi::CreateBounded)
+                .map(|(_, _, l)| *l)
+                .collect();
+
+            // Which spawner-frame channel each closure upvar field is bound
+            // to, so a recv/send inside the closure can be matched back to
+            // the channel it shares with the spawner.
+            let mut capture_channel: HashMap<usize, Local> = HashMap::new();
+            for (idx, &captured_local) in spawn.captures.iter().enumerate() {
+                if let Some(&create_local) = spawner_creates.iter().find(|&&c| Self::traces_to(spawner_body, captured_local, c)) {
+                    capture_channel.insert(idx, create_local);
+                }
+            }
+            let closure_channel = |local: Local| -> Option<Local> { capture_channel.get(&Self::capture_field(closure_body, local)?).copied() };
+
+            let mut spawner_recv: HashMap<Local, Location> = HashMap::new();
+            let mut spawner_send: HashMap<Local, Location> = HashMap::new();
+            for &(api, loc, local) in spawner_sites {
+                let Some(&create_local) = spawner_creates.iter().find(|&&c| Self::traces_to(spawner_body, local, c)) else {
+                    continue;
+                };
+                match api {
+                    ChanApi::Recv => {
+                        spawner_recv.entry(create_local).or_insert(loc);
+                    }
+                    ChanApi::Send => {
+                        spawner_send.entry(create_local).or_insert(loc);
+                    }
+                    _ => {}
+                }
+            }
+            let mut closure_recv: HashMap<Local, Location> = HashMap::new();
+            let mut closure_send: HashMap<Local, Location> = HashMap::new();
+            for &(api, loc, local) in closure_sites {
+                let Some(create_local) = closure_channel(local) else { continue };
+                match api {
+                    ChanApi::Recv => {
+                        closure_recv.entry(create_local).or_insert(loc);
+                    }
+                    ChanApi::Send => {
+                        closure_send.entry(create_local).or_insert(loc);
+                    }
+                    _ => {}
+                }
+            }
+
+            let spawner_dominators = spawner_body.basic_blocks.dominators();
+            let closure_dominators = closure_body.basic_blocks.dominators();
+
+            // Does this side already have its own complementary op (a send
+            // clearing the other side's recv, or a recv clearing the other
+            // side's send) on a channel the other side needs, guaranteed
+            // (by dominance, in this side's own body) to run before
+            // `before_loc`? If so, this side clears its half of the
+            // potential cycle before it can itself block at `before_loc` --
+            // there's no real cross-thread wait-for edge there, only a
+            // channel-identity coincidence. This is what tells apart a real
+            // cycle (each side blocks on its own recv before ever reaching
+            // its own unblocking send) from a fixture like
+            // `toys/channel-deadlock`, where one side's send always fires
+            // before that same side's own recv.
+            let own_op_clears_other = |complementary: &HashMap<Local, Location>, other_needs: &HashMap<Local, Location>, dominators: &Dominators<BasicBlock>, before_loc: Location| -> bool {
+                complementary.iter().any(|(channel, &op_loc)| {
+                    other_needs.contains_key(channel) && op_loc.block != before_loc.block && dominators.dominates(op_loc.block, before_loc.block)
+                })
+            };
+
+            let depends = |recv: &HashMap<Local, Location>,
+                           send: &HashMap<Local, Location>,
+                           other_recv: &HashMap<Local, Location>,
+                           other_send: &HashMap<Local, Location>,
+                           dominators: &Dominators<BasicBlock>|
+             -> Option<Location> {
+                for (channel, &recv_loc) in recv {
+                    if !other_send.contains_key(channel) {
+                        continue;
+                    }
+                    if own_op_clears_other(send, other_recv, dominators, recv_loc) {
+                        continue;
+                    }
+                    return Some(recv_loc);
+                }
+                for (channel, &send_loc) in send {
+                    if !(bounded_creates.contains(channel) && other_recv.contains_key(channel)) {
+                        continue;
+                    }
+                    if own_op_clears_other(recv, other_send, dominators, send_loc) {
+                        continue;
+                    }
+                    return Some(send_loc);
+                }
+                None
+            };
+
+            let spawner_blocks_on = depends(&spawner_recv, &spawner_send, &closure_recv, &closure_send, &spawner_dominators);
+            let closure_blocks_on = depends(&closure_recv, &closure_send, &spawner_recv, &spawner_send, &closure_dominators);
+
+            if let (Some(spawner_loc), Some(closure_loc)) = (spawner_blocks_on, closure_blocks_on) {
+                let source_map = self.tcx.sess.source_map();
+                let diagnosis = ChannelDeadlockDiagnosis {
+                    recv: source_map.span_to_diagnostic_string(spawner_body[spawner_loc.block].terminator().source_info.span),
+                    excluding_branch: source_map.span_to_diagnostic_string(closure_body[closure_loc.block].terminator().source_info.span),
+                };
+                let report_content = ReportContent::new(
+                    "CrossThreadChannelDeadlock".to_owned(),
+                    "Possibly".to_owned(),
+                    diagnosis,
+                    "The spawning thread and the thread spawned here each block waiting on the \
+                     other: one side's blocking channel op (a recv, or -- on a bounded/rendezvous \
+                     channel -- a send) can only be unblocked by an op the other side only \
+                     reaches after its own block clears. Neither side has a send left that could \
+                     break the cycle."
+                        .to_owned(),
+                );
+                reports.push(Report::ChannelDeadlock(report_content));
+            }
+        }
+        reports
+    }
+
+    /// Best-effort literal capacity of the `sync_channel(n)` call at
+    /// `loc` -- `None` for a non-constant bound. Only used to annotate a
+    /// report; a non-constant bound doesn't suppress the finding, the
+    /// same "flag the pattern, don't require proving the literal" stance
+    /// `ChanApi::CreateBounded`'s own doc comment already takes on the
+    /// bound.
+    fn sync_channel_capacity(body: &Body<'tcx>, loc: Location) -> Option<u64> {
+        let TerminatorKind::Call { args, .. } = &body[loc.block].terminator().kind else { return None };
+        let arg = args.first()?;
+        let Operand::Constant(box rustc_middle::mir::Constant { literal, .. }) = arg else {
+            return None;
+        };
+        literal.try_to_scalar_int()?.try_to_u64().ok()
+    }
+
+    /// A producer/consumer pipeline over a bounded `sync_channel(n)`,
+    /// where the producer's `send` can block once the buffer fills while
+    /// the consumer is also waiting on a *second*, distinct channel edge
+    /// with the producer on the other end of it -- the reader/parser
+    /// pipeline this request names, generalized to any two channels
+    /// rather than assuming a specific queue-depth atomic. If the
+    /// consumer has no way to drain the bounded channel except after
+    /// first getting past that second wait, and the producer has no way
+    /// past its own full-buffer wait except via the consumer draining
+    /// it, neither side can make progress.
+    ///
+    /// Reuses the spawner/closure two-node model and capture-mapping
+    /// `detect_cross_thread` already built for the simpler single-edge
+    /// case, tried in both directions (either side of the spawn can be
+    /// the producer). Unlike `detect_cross_thread`'s own cycle check,
+    /// this one requires the two edges to land on *different* channels:
+    /// the same producer/consumer pair waiting on each other over one
+    /// and the same channel is just that channel's fullness/emptiness
+    /// invariant at work, not a deadlock -- distinguishing that is the
+    /// "safe case" this request asks for.
+    fn detect_backpressure_deadlock(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        sites_by_caller: &HashMap<InstanceId, Vec<(ChanApi, Location, Local)>>,
+    ) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for spawn in self.find_spawn_sites(callgraph) {
+            let (Some(spawner_sites), Some(closure_sites)) = (sites_by_caller.get(&spawn.spawner), sites_by_caller.get(&spawn.closure)) else {
+                continue;
+            };
+
+            let spawner_inst = callgraph.index_to_instance(spawn.spawner).unwrap().instance();
+            let spawner_body = self.tcx.instance_mir(spawner_inst.def);
+            let closure_inst = callgraph.index_to_instance(spawn.closure).unwrap().instance();
+            let closure_body = self.tcx.instance_mir(closure_inst.def);
+
+            let spawner_creates: Vec<Local> = spawner_sites
+                .iter()
+                .filter(|(api, ..)| matches!(api, ChanApi::Create | ChanApi::CreateBounded))
+                .map(|(_, _, l)| *l)
+                .collect();
+            let bounded_capacity: HashMap<Local, Option<u64>> = spawner_sites
+                .iter()
+                .filter(|(api, ..)| *api == ChanApi::CreateBounded)
+                .map(|(_, loc, l)| (*l, Self::sync_channel_capacity(spawner_body, *loc)))
+                .collect();
+
+            let mut capture_channel: HashMap<usize, Local> = HashMap::new();
+            for (idx, &captured_local) in spawn.captures.iter().enumerate() {
+                if let Some(&create_local) = spawner_creates.iter().find(|&&c| Self::traces_to(spawner_body, captured_local, c)) {
+                    capture_channel.insert(idx, create_local);
+                }
+            }
+            let closure_channel = |local: Local| -> Option<Local> { capture_channel.get(&Self::capture_field(closure_body, local)?).copied() };
+
+            let mut spawner_recv: HashMap<Local, Location> = HashMap::new();
+            let mut spawner_send: HashMap<Local, Location> = HashMap::new();
+            for &(api, loc, local) in spawner_sites {
+                let Some(&create_local) = spawner_creates.iter().find(|&&c| Self::traces_to(spawner_body, local, c)) else {
+                    continue;
+                };
+                match api {
+                    ChanApi::Recv => {
+                        spawner_recv.entry(create_local).or_insert(loc);
+                    }
+                    ChanApi::Send => {
+                        spawner_send.entry(create_local).or_insert(loc);
+                    }
+                    _ => {}
+                }
+            }
+            let mut closure_recv: HashMap<Local, Location> = HashMap::new();
+            let mut closure_send: HashMap<Local, Location> = HashMap::new();
+            for &(api, loc, local) in closure_sites {
+                let Some(create_local) = closure_channel(local) else { continue };
+                match api {
+                    ChanApi::Recv => {
+                        closure_recv.entry(create_local).or_insert(loc);
+                    }
+                    ChanApi::Send => {
+                        closure_send.entry(create_local).or_insert(loc);
+                    }
+                    _ => {}
+                }
+            }
+
+            // Tried in both directions: spawner-as-producer/closure-as-
+            // consumer, and the other way around.
+            let directions = [
+                (&spawner_send, &spawner_recv, spawner_body, &closure_recv, &closure_send, closure_body),
+                (&closure_send, &closure_recv, closure_body, &spawner_recv, &spawner_send, spawner_body),
+            ];
+            for &(producer_send, producer_recv, producer_body, consumer_recv, consumer_send, consumer_body) in &directions {
+                for (&bounded_chan, &capacity) in &bounded_capacity {
+                    let Some(&producer_send_loc) = producer_send.get(&bounded_chan) else { continue };
+                    if !consumer_recv.contains_key(&bounded_chan) {
+                        continue;
+                    }
+                    // The second, distinct edge: the consumer also waits
+                    // on some other channel the producer is the other
+                    // end of.
+                    for (&other_chan, &consumer_wait_loc) in consumer_recv.iter().chain(consumer_send.iter()) {
+                        if other_chan == bounded_chan {
+                            continue;
+                        }
+                        if !(producer_send.contains_key(&other_chan) || producer_recv.contains_key(&other_chan)) {
+                            continue;
+                        }
+                        // Safe case: the consumer has a way to drain the
+                        // bounded channel that isn't sequenced after this
+                        // wait -- i.e. the drain doesn't always come
+                        // after it. With only one recorded drain site per
+                        // channel (the maps above keep the first), "always"
+                        // reduces to "this one site, if it exists, is
+                        // dominated by the wait".
+                        let dominators = consumer_body.basic_blocks.dominators();
+                        let always_blocks_first = consumer_recv
+                            .get(&bounded_chan)
+                            .is_some_and(|&drain_loc| dominators.dominates(consumer_wait_loc.block, drain_loc.block));
+                        if !always_blocks_first {
+                            continue;
+                        }
+                        let source_map = self.tcx.sess.source_map();
+                        let capacity_note = match capacity {
+                            Some(n) => format!("capacity {n}"),
+                            None => "a non-constant capacity".to_owned(),
+                        };
+                        let diagnosis = ChannelDeadlockDiagnosis {
+                            recv: source_map.span_to_diagnostic_string(producer_body[producer_send_loc.block].terminator().source_info.span),
+                            excluding_branch: source_map.span_to_diagnostic_string(consumer_body[consumer_wait_loc.block].terminator().source_info.span),
+                        };
+                        let report_content = ReportContent::new(
+                            "ChannelBackpressureDeadlock".to_owned(),
+                            "Possibly".to_owned(),
+                            diagnosis,
+                            format!(
+                                "This send is on a bounded sync_channel ({capacity_note}) whose only \
+                                 reader is blocked on a second, distinct channel edge before it ever \
+                                 drains this one -- if the buffer fills, this send blocks waiting for \
+                                 that reader, and the reader can't reach the recv that would drain it \
+                                 until its own wait on the other edge resolves, which in turn needs \
+                                 this send to get unblocked first."
+                            ),
+                        );
+                        reports.push(Report::ChannelDeadlock(report_content));
+                    }
+                }
+            }
+        }
+        reports
+    }
+}
+
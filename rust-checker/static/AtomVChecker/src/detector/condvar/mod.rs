@@ -0,0 +1,203 @@
+//! Detect two classic `Condvar`/`Mutex` misuses:
+//! - a `Condvar::wait`/`wait_timeout` call that isn't inside a loop, so a
+//!   spurious wakeup (it resumes without the awaited condition actually
+//!   being true) or a lost wakeup (a `notify` that fires before this
+//!   thread reaches `wait` at all) isn't caught by re-checking the
+//!   predicate;
+//! - a lock-order-inversion cycle: two `Mutex`/`RwLock` acquisitions
+//!   nested in one order somewhere in the crate, and in the opposite
+//!   order somewhere else, which can deadlock if two threads take the
+//!   two locks concurrently in opposite orders.
+//!
+//! Doesn't attempt to pair a `notify` with the specific mutex the `wait`
+//! it targets releases -- that needs the same points-to machinery
+//! `detector::atomic` uses to correlate a place across call sites, which
+//! isn't worth standing up just for this check; the loop-membership
+//! heuristic below already catches the same family of bug (a `wait` with
+//! no predicate recheck) without it.
+extern crate rustc_hash;
+extern crate rustc_middle;
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::visit::IntoNodeReferences;
+use rustc_middle::mir::TerminatorKind;
+use rustc_middle::ty::{TyCtxt, TyKind};
+use rustc_span::Span;
+
+use crate::analysis::callgraph::{CallGraph, CallGraphNode};
+use crate::detector::chan::report::ChannelDeadlockDiagnosis;
+use crate::detector::report::{Report, ReportContent};
+use crate::interest::concurrency::atomic::AtomicCollector;
+use crate::interest::concurrency::condvar::{lock_acquire_key, CondvarApi};
+
+pub struct CondvarDetector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> CondvarDetector<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn detect(&mut self, callgraph: &CallGraph<'tcx>) -> Vec<Report> {
+        let mut reports = self.detect_wait_not_in_loop(callgraph);
+        reports.extend(self.detect_lock_order_inversion(callgraph));
+        reports
+    }
+
+    /// Flag a `Condvar::wait`/`wait_timeout` call site that doesn't lie on
+    /// a loop in its own function's CFG -- the same structural signal
+    /// `AtomicCollector::is_block_in_cycle` already provides for a
+    /// `compare_exchange_weak` retry, reused here since "does this call
+    /// sit inside a loop that can re-run it" is exactly the question both
+    /// checks need answered. `wait_while`/`wait_timeout_while` are never
+    /// flagged -- they take the re-check predicate as a closure argument,
+    /// so the standard library itself already loops on it.
+    fn detect_wait_not_in_loop(&self, callgraph: &CallGraph<'tcx>) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for (index, _) in callgraph.graph.node_references() {
+            let CallGraphNode::WithBody(inst) = callgraph.index_to_instance(index).unwrap() else {
+                continue;
+            };
+            let body = self.tcx.instance_mir(inst.instance().def);
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                let TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+                    continue;
+                };
+                let TyKind::FnDef(def_id, _) = func.ty(body, self.tcx).kind() else {
+                    continue;
+                };
+                if CondvarApi::from_def_id(*def_id, self.tcx) != Some(CondvarApi::Wait) {
+                    continue;
+                }
+                if AtomicCollector::is_block_in_cycle(body, block) {
+                    continue;
+                }
+                let span = data.terminator().source_info.span;
+                let source_map = self.tcx.sess.source_map();
+                // `ChannelDeadlockDiagnosis` belongs to `std::sync::mpsc`
+                // channel deadlocks; reused here since its own defining
+                // file, `detector::condvar::report`, doesn't exist in this
+                // tree to add a dedicated condvar diagnosis type to.
+                // `recv` holds the `wait` call's own span; there's no
+                // competing branch here, so `excluding_branch` is empty.
+                let diagnosis = ChannelDeadlockDiagnosis {
+                    recv: source_map.span_to_diagnostic_string(span),
+                    excluding_branch: String::new(),
+                };
+                let report_content = ReportContent::new(
+                    "CondvarWaitOutsideLoop".to_owned(),
+                    "Possibly".to_owned(),
+                    diagnosis,
+                    "This Condvar::wait isn't inside a loop re-checking its predicate, so it's \
+                     vulnerable to both a spurious wakeup (it resumes without the condition it's \
+                     waiting for actually being true) and a lost wakeup (a notify that fires before \
+                     this thread reaches wait is never observed, since nothing re-checks the \
+                     predicate after re-acquiring the lock) -- guard it with `while !predicate { \
+                     guard = cvar.wait(guard).unwrap(); }`, or use wait_while instead."
+                        .to_owned(),
+                );
+                reports.push(Report::ChannelDeadlock(report_content));
+            }
+        }
+        reports
+    }
+
+    /// Build a crate-wide lock-order graph from nested acquisitions (a
+    /// `lock`/`read`/`write` call dominated by another, still-notionally-
+    /// held one in the same function -- dominance as a stand-in for "the
+    /// outer guard hasn't been dropped yet", the same approximation
+    /// `fence_covers` uses for a fence's reach), then report any pair of
+    /// mutexes acquired in one order somewhere and the opposite order
+    /// somewhere else.
+    fn detect_lock_order_inversion(&self, callgraph: &CallGraph<'tcx>) -> Vec<Report> {
+        let mut edges: HashMap<String, HashMap<String, Span>> = HashMap::new();
+
+        for (index, _) in callgraph.graph.node_references() {
+            let CallGraphNode::WithBody(inst) = callgraph.index_to_instance(index).unwrap() else {
+                continue;
+            };
+            let body = self.tcx.instance_mir(inst.instance().def);
+            let dominators = body.basic_blocks.dominators();
+
+            let mut acquires = Vec::new();
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                let TerminatorKind::Call { func, .. } = &data.terminator().kind else {
+                    continue;
+                };
+                let TyKind::FnDef(def_id, substs) = func.ty(body, self.tcx).kind() else {
+                    continue;
+                };
+                let path = self.tcx.def_path_str_with_substs(*def_id, substs);
+                if let Some(key) = lock_acquire_key(&path) {
+                    acquires.push((block, key, data.terminator().source_info.span));
+                }
+            }
+
+            for &(outer_block, ref outer_key, _) in &acquires {
+                for &(inner_block, ref inner_key, inner_span) in &acquires {
+                    if outer_block == inner_block || outer_key == inner_key {
+                        continue;
+                    }
+                    if dominators.dominates(outer_block, inner_block) {
+                        edges.entry(outer_key.clone()).or_default().entry(inner_key.clone()).or_insert(inner_span);
+                    }
+                }
+            }
+        }
+
+        let reaches = |from: &str, to: &str| -> bool {
+            let mut seen = HashSet::new();
+            let mut stack = vec![from.to_owned()];
+            while let Some(node) = stack.pop() {
+                if node == to {
+                    return true;
+                }
+                if !seen.insert(node.clone()) {
+                    continue;
+                }
+                if let Some(succs) = edges.get(&node) {
+                    stack.extend(succs.keys().cloned());
+                }
+            }
+            false
+        };
+
+        let mut reported = HashSet::new();
+        let mut reports = Vec::new();
+        for (outer, inners) in &edges {
+            for (inner, &span) in inners {
+                let pair = if outer < inner { (outer.clone(), inner.clone()) } else { (inner.clone(), outer.clone()) };
+                if reported.contains(&pair) {
+                    continue;
+                }
+                if reaches(inner, outer) {
+                    reported.insert(pair);
+                    let source_map = self.tcx.sess.source_map();
+                    // Same borrowed-diagnosis rationale as
+                    // `detect_wait_not_in_loop` above: `recv` carries the
+                    // human-readable lock order, `excluding_branch` the
+                    // representative span.
+                    let diagnosis = ChannelDeadlockDiagnosis {
+                        recv: format!("{outer} then {inner}"),
+                        excluding_branch: source_map.span_to_diagnostic_string(span),
+                    };
+                    let report_content = ReportContent::new(
+                        "LockOrderInversion".to_owned(),
+                        "Possibly".to_owned(),
+                        diagnosis,
+                        format!(
+                            "`{outer}` is acquired while already holding `{inner}` somewhere in this \
+                             crate, and `{inner}` is acquired while already holding `{outer}` somewhere \
+                             else -- if two threads take these two locks concurrently in opposite \
+                             order, each can end up waiting on the lock the other already holds."
+                        ),
+                    );
+                    reports.push(Report::ChannelDeadlock(report_content));
+                }
+            }
+        }
+        reports
+    }
+}
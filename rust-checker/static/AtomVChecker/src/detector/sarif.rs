@@ -0,0 +1,151 @@
+//! Minimal SARIF 2.1.0 emitter, so findings can feed GitHub code scanning
+//! and other SARIF-consuming tooling alongside the existing pretty-JSON and
+//! rustc-style `Diagnostic` outputs (see `detector::atomic::diagnostic`).
+use serde::Serialize;
+
+use crate::detector::atomic::diagnostic::Diagnostic;
+use crate::detector::report::Report;
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifDriver {
+    pub name: &'static str,
+    #[serde(rename = "informationUri")]
+    pub information_uri: &'static str,
+    pub version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifResult {
+    #[serde(rename = "ruleId")]
+    pub rule_id: String,
+    pub level: String,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifLocation {
+    #[serde(rename = "physicalLocation")]
+    pub physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifPhysicalLocation {
+    #[serde(rename = "artifactLocation")]
+    pub artifact_location: SarifArtifactLocation,
+    pub region: SarifRegion,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifRegion {
+    #[serde(rename = "startLine")]
+    pub start_line: u32,
+    #[serde(rename = "startColumn")]
+    pub start_column: u32,
+    #[serde(rename = "endLine")]
+    pub end_line: u32,
+    #[serde(rename = "endColumn")]
+    pub end_column: u32,
+}
+
+/// The `kind` a `ReportContent` was built with, read back out of the
+/// serialized form -- see `ui_test::report_kind`, which this mirrors for
+/// the same reason: `detector::condvar`/`detector::broadcast` both borrow
+/// `Report::ChannelDeadlock`, so the outer variant alone can't tell their
+/// findings apart from a real `mpsc` deadlock.
+fn report_kind(report: &Report) -> Option<String> {
+    let value = serde_json::to_value(report).ok()?;
+    let inner = value.as_object()?.values().next()?;
+    inner.get("kind")?.as_str().map(str::to_owned)
+}
+
+/// Rule id for a report -- the same "what kind of finding is this" tag
+/// `ui_test`'s `//~` fixtures annotate with, just lower_snake_case for
+/// SARIF's convention.
+fn rule_id(report: &Report) -> &'static str {
+    match report_kind(report).as_deref() {
+        Some("CondvarWaitOutsideLoop") => return "condvar_wait_outside_loop",
+        Some("LockOrderInversion") => return "lock_order_inversion",
+        Some("BroadcastSlowReceiver") => return "broadcast_slow_receiver",
+        _ => {}
+    }
+    match report {
+        Report::AtomicCorrelationViolation(_) => "atomic_correlation_violation",
+        Report::ChannelDeadlock(_) => "channel_deadlock",
+    }
+}
+
+/// Build one SARIF run covering every report from a single crate's
+/// analysis. Spans are recovered the same way `Diagnostic::from_report`
+/// recovers them (by reading the rendered `span_to_diagnostic_string` text
+/// back out of the report's own serialized form), so a finding with no
+/// recoverable span is simply omitted rather than emitted with a bogus
+/// location.
+pub fn to_sarif(reports: &[Report]) -> SarifLog {
+    let results = reports
+        .iter()
+        .filter_map(|report| {
+            let diagnostic = Diagnostic::from_report(report, None)?;
+            let span = diagnostic.spans.first()?;
+            Some(SarifResult {
+                rule_id: rule_id(report).to_owned(),
+                level: "warning".to_owned(),
+                message: SarifMessage { text: diagnostic.message },
+                locations: vec![SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation { uri: span.file_name.clone() },
+                        region: SarifRegion {
+                            start_line: span.line_start,
+                            start_column: span.column_start,
+                            end_line: span.line_end,
+                            end_column: span.column_end,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+    SarifLog {
+        schema: "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json",
+        version: "2.1.0",
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "atomvchecker",
+                    information_uri: "https://github.com/K-Jay-3000/Chain-Fox",
+                    version: "0.1.0",
+                },
+            },
+            results,
+        }],
+    }
+}
@@ -7,11 +7,12 @@ use std::cmp::{Ordering, PartialOrd};
 use std::collections::HashMap;
 use crate::analysis::datadep;
 use crate::detector::atomic;
-use rustc_middle::mir::{Body, Local, Place, TerminatorKind, Operand, StatementKind, Rvalue, BasicBlockData, PlaceRef};
+use rustc_middle::mir::{Body, Local, Place, TerminatorKind, Operand, StatementKind, Rvalue, PlaceRef};
 use crate::analysis::pointsto::{AliasAnalysis, AliasId, ConstraintNode};
 use rustc_middle::ty::{Instance, TyCtxt, self};
 use crate::analysis::callgraph::{CallGraph, InstanceId};
 use petgraph::Direction::Incoming;
+use rustc_span::Span;
 
 
 #[cfg(test)]
@@ -44,25 +45,37 @@ pub enum AtomicOrd {
     Acquire,
     Release,
     Relaxed,
+    /// The `Ordering` argument isn't a local constant and couldn't be
+    /// traced back to one through the data-dependency graph (e.g. it comes
+    /// in as a function parameter, or off a runtime `match`): it's
+    /// genuinely user/caller-controlled, so the atomic is still collected
+    /// rather than silently dropped, but it can't be placed in the lattice.
+    Dynamic,
 }
 
+/// The real C++/LLVM ordering lattice:
+/// `Relaxed < {Acquire, Release} < AcqRel < SeqCst`, with `Acquire` and
+/// `Release` themselves incomparable (neither implies the other). Prior to
+/// this, `Acquire == Release` was conflated as `Equal`, which made
+/// "strong enough" comparisons against a required ordering silently wrong
+/// whenever the two sides disagreed on read-vs-write direction.
 impl PartialOrd for AtomicOrd {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         use AtomicOrd::*;
         match (*self, *other) {
+            (Dynamic, _) | (_, Dynamic) => None,
             (SeqCst, SeqCst)
             | (AcqRel, AcqRel)
             | (Acquire, Acquire)
-            | (Acquire, Release)
-            | (Release, Acquire)
-            | (Release, Release) 
-            | (Relaxed, Relaxed)=> Some(Ordering::Equal),
-            (SeqCst, _) | (Acquire, Relaxed) | (Release, Relaxed) | (AcqRel, Relaxed) | (AcqRel, Acquire) | (AcqRel, Release)=> {
-                Some(Ordering::Greater)
-            }
-            (_, SeqCst) | (Relaxed, Release) | (Relaxed, Acquire) | (Relaxed, AcqRel) | (Acquire, AcqRel) | (Release, AcqRel)=> {
-                Some(Ordering::Less)
-            }
+            | (Release, Release)
+            | (Relaxed, Relaxed) => Some(Ordering::Equal),
+            (Acquire, Release) | (Release, Acquire) => None,
+            (SeqCst, _) => Some(Ordering::Greater),
+            (_, SeqCst) => Some(Ordering::Less),
+            (AcqRel, Acquire) | (AcqRel, Release) | (AcqRel, Relaxed) => Some(Ordering::Greater),
+            (Acquire, AcqRel) | (Release, AcqRel) | (Relaxed, AcqRel) => Some(Ordering::Less),
+            (Acquire, Relaxed) | (Release, Relaxed) => Some(Ordering::Greater),
+            (Relaxed, Acquire) | (Relaxed, Release) => Some(Ordering::Less),
         }
     }
 }
@@ -74,23 +87,42 @@ impl Default for AtomicOrd {
 }
 
 impl AtomicOrd {
-    fn from_ordering<'a>(basic_block_data: &'a BasicBlockData<'a>, place: &Place) -> Option<Self> {
-        for statement in &basic_block_data.statements {
-            if let StatementKind::Assign(box (assigned_place, rvalue)) = &statement.kind {
-                if assigned_place.local == place.local {
-                    if let Rvalue::Use(operand) = rvalue {
-                        if let Operand::Constant(_) = operand {
-                            let ordering = format!("{:?}", operand);
-                            if ordering.ends_with("Relaxed") {
-                                return Some(AtomicOrd::Relaxed);
-                            } else if ordering.ends_with("Acquire") {
-                                return Some(AtomicOrd::Acquire);
-                            } else if ordering.ends_with("Release") {
-                                return Some(AtomicOrd::Release);
-                            } else if ordering.ends_with("AcqRel") {
-                                return Some(AtomicOrd::AcqRel);
-                            } else if ordering.ends_with("SeqCst") {
-                                return Some(AtomicOrd::SeqCst);
+    /// `std::sync::atomic::Ordering`'s discriminants in declaration order
+    /// (`Relaxed, Release, Acquire, AcqRel, SeqCst`), used to read the
+    /// ordering off a constant's evaluated scalar value rather than
+    /// string-matching its `Debug` output.
+    fn from_discriminant(discr: u8) -> Option<Self> {
+        match discr {
+            0 => Some(AtomicOrd::Relaxed),
+            1 => Some(AtomicOrd::Release),
+            2 => Some(AtomicOrd::Acquire),
+            3 => Some(AtomicOrd::AcqRel),
+            4 => Some(AtomicOrd::SeqCst),
+            _ => None,
+        }
+    }
+
+    fn from_operand(operand: &Operand<'_>) -> Option<Self> {
+        let Operand::Constant(box rustc_middle::mir::Constant { literal, .. }) = operand else {
+            return None;
+        };
+        let discr = literal.try_to_scalar_int()?.try_to_u8().ok()?;
+        Self::from_discriminant(discr)
+    }
+
+    /// Find the constant assigned (directly, via `Rvalue::Use`) to
+    /// `place` anywhere in `body`, not just the block containing the call:
+    /// the ordering argument is routinely materialized a few statements
+    /// earlier, or in a predecessor block, and then just moved/copied into
+    /// the call's argument list.
+    fn from_local_assignment<'tcx>(body: &Body<'tcx>, local: Local) -> Option<Self> {
+        for bb in body.basic_blocks.iter() {
+            for statement in &bb.statements {
+                if let StatementKind::Assign(box (assigned_place, rvalue)) = &statement.kind {
+                    if assigned_place.local == local {
+                        if let Rvalue::Use(operand) = rvalue {
+                            if let Some(ord) = Self::from_operand(operand) {
+                                return Some(ord);
                             }
                         }
                     }
@@ -100,7 +132,59 @@ impl AtomicOrd {
         None
     }
 
-    
+    /// Resolve the `Ordering` passed through `place`. Tries a direct
+    /// constant assignment first; if the place is instead fed by a move,
+    /// copy, or other data-dependency chain (an intervening temp, a
+    /// `let ordering = ...;` a few statements up, etc.), walks the
+    /// data-dependency graph backward for the defining constant. Returns
+    /// [`AtomicOrd::Dynamic`] rather than `None` when the ordering is
+    /// genuinely runtime-chosen (a function parameter, the arm of a
+    /// `match`, ...), so the atomic is still collected and flagged as
+    /// user-controlled instead of silently dropped.
+    pub fn from_ordering<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        callgraph: &CallGraph<'tcx>,
+        caller_instance: InstanceId,
+        body: &Body<'tcx>,
+        place: &Place<'tcx>,
+    ) -> Self {
+        if let Some(ord) = Self::from_local_assignment(body, place.local) {
+            return ord;
+        }
+        let data_deps = datadep::data_deps(body);
+        let deps = datadep::all_data_dep_on(place.local, &data_deps, callgraph, caller_instance, body, tcx);
+        for dep in deps {
+            if let Some(ord) = Self::from_local_assignment(body, dep) {
+                return ord;
+            }
+        }
+        AtomicOrd::Dynamic
+    }
+
+    /// Whether `self` is at least as strong as `required` in the ordering
+    /// lattice (`Equal` counts as satisfying it). A `Dynamic` ordering is
+    /// incomparable to everything, so it never satisfies a requirement.
+    pub fn is_at_least(self, required: AtomicOrd) -> bool {
+        matches!(self.partial_cmp(&required), Some(Ordering::Equal) | Some(Ordering::Greater))
+    }
+
+    /// Least upper bound of two orderings in the lattice: the weakest
+    /// ordering that is at least as strong as both `a` and `b`. `Acquire`
+    /// joined with `Release` is `AcqRel`, since that's the weakest ordering
+    /// implying both. `Dynamic`'s real strength is unknown, so it joins to
+    /// `SeqCst` rather than understating the combined requirement.
+    pub fn join(a: AtomicOrd, b: AtomicOrd) -> AtomicOrd {
+        use AtomicOrd::*;
+        match (a, b) {
+            (Dynamic, _) | (_, Dynamic) => SeqCst,
+            (SeqCst, _) | (_, SeqCst) => SeqCst,
+            (AcqRel, _) | (_, AcqRel) => AcqRel,
+            (Acquire, Release) | (Release, Acquire) => AcqRel,
+            (Acquire, Acquire) => Acquire,
+            (Release, Release) => Release,
+            (Relaxed, x) | (x, Relaxed) => x,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Copy)]
@@ -114,7 +198,29 @@ pub enum AtomicInstructions {
 impl AtomicInstructions {
     pub fn from_instance<'tcx>(instance: Instance<'tcx>, tcx: TyCtxt<'tcx>) -> Option<Self> {
         let path = tcx.def_path_str_with_substs(instance.def_id(), instance.substs);
-        let rmw_operations = ["swap", "fetch_add", "fetch_sub", "fetch_max", "fetch_or", "fetch_xor", "fetch_min", "compare_and_swap"];
+        Self::from_path(&path)
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        let rmw_operations = [
+            "swap",
+            "fetch_add",
+            "fetch_sub",
+            "fetch_max",
+            "fetch_or",
+            "fetch_and",
+            "fetch_xor",
+            "fetch_min",
+            "fetch_nand",
+            "compare_and_swap",
+            // Strict-provenance `AtomicPtr` arithmetic: same "self, value,
+            // ordering" shape as `fetch_add`/`swap` above, just operating on
+            // the pointer's address instead of an integer's value.
+            "fetch_byte_add",
+            "fetch_byte_sub",
+            "fetch_ptr_add",
+            "fetch_ptr_sub",
+        ];
         if path.ends_with("compare_exchange") || path.ends_with("compare_exchange_weak") || path.ends_with("fetch_update") {
             Some(AtomicInstructions::CompareExchange)
         } else if path.ends_with("load") {
@@ -129,6 +235,78 @@ impl AtomicInstructions {
     }
 }
 
+/// Which form a plain `compare_exchange[_weak]` call site used. Doesn't
+/// apply to `fetch_update` -- its own stdlib implementation already
+/// retries internally via `compare_exchange_weak`, so it isn't a strong-
+/// vs-weak choice this call site made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CasForm {
+    Strong,
+    Weak,
+}
+
+/// Whether an atomic operation lowers to a native lock-free instruction or
+/// to a sharded spinlock (the path the `atomic` crate and, for
+/// non-natively-sized types, `crossbeam_utils::atomic::AtomicCell` take).
+/// Downstream passes shouldn't reason about memory ordering as if a
+/// spinlock-backed "atomic" were actually lock-free: its observable
+/// ordering is whatever the lock gives it, regardless of the `Ordering`
+/// argument passed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AtomicBacking {
+    LockFree,
+    Spinlock,
+}
+
+/// Third-party atomic wrapper crates `atomic_backing` classifies: the
+/// generic `atomic::Atomic<T>` (Amanieu) and `crossbeam_utils::atomic::
+/// AtomicCell<T>`. Both lower every method onto a sharded, cache-line-
+/// aligned global spinlock when `T` has no native lock-free support. Only
+/// `atomic::Atomic<T>` call sites are collected as atomic instructions by
+/// `detector::atomic`'s discovery regexes; `AtomicCell<T>` calls are not
+/// collected, so they never reach this classification.
+const THIRD_PARTY_ATOMIC_WRAPPERS: [&str; 2] = ["atomic::Atomic::<", "crossbeam_utils::atomic::AtomicCell::<"];
+
+/// Classify an operation's backing (native lock-free instruction, or a
+/// third-party wrapper's spinlock) from its `def_path_str` and, for a
+/// third-party wrapper, `instance`'s own concrete `T`. `std::sync::
+/// atomic::Atomic{Usize,Bool,Ptr,..}` methods are always lock-free. For
+/// the `atomic`/`crossbeam_utils` wrappers, `T`'s layout decides: both
+/// crates dispatch to a real lock-free instruction only when `T` is one
+/// of the naturally aligned native widths, falling back to a sharded
+/// spinlock otherwise. `instance` is already monomorphized by the time a
+/// call site reaches this analysis, so `tcx.layout_of` on its first type
+/// parameter gives that concrete answer; a `T` that's still generic here,
+/// or whose layout can't be computed, falls back to the conservative
+/// `Spinlock` this function always returned before.
+pub fn atomic_backing<'tcx>(tcx: TyCtxt<'tcx>, path: &str, instance: Instance<'tcx>) -> AtomicBacking {
+    if !THIRD_PARTY_ATOMIC_WRAPPERS.iter().any(|wrapper| path.starts_with(wrapper)) {
+        return AtomicBacking::LockFree;
+    }
+    let Some(wrapped_ty) = instance.substs.types().next() else {
+        return AtomicBacking::Spinlock;
+    };
+    match tcx.layout_of(ty::ParamEnv::reveal_all().and(wrapped_ty)) {
+        // The widths every mainstream target has a native atomic
+        // instruction for, naturally aligned -- the same condition the
+        // `atomic`/`crossbeam_utils` wrappers themselves dispatch on.
+        Ok(layout) if matches!(layout.size.bytes(), 1 | 2 | 4 | 8) && layout.align.abi.bytes() >= layout.size.bytes() => {
+            AtomicBacking::LockFree
+        }
+        _ => AtomicBacking::Spinlock,
+    }
+}
+
+/// Maximum body size (basic blocks, plain statements) for a function to be
+/// treated as a trivial forwarding wrapper that conservative inlining
+/// should splice into its caller, mirroring rustc's own MIR inliner's
+/// size-based cost heuristic.
+const MAX_WRAPPER_BLOCKS: usize = 3;
+const MAX_WRAPPER_STATEMENTS: usize = 8;
+/// Bound on how many wrapper frames to climb through, so a long chain of
+/// trivial forwarders can't blow up analysis time.
+const MAX_INLINE_DEPTH: usize = 4;
+
 pub struct AtomicCollector<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
     instance_id: InstanceId,
@@ -149,37 +327,245 @@ impl<'a, 'tcx> AtomicCollector<'a, 'tcx>{
         }
     }
 
+    /// A small body whose only call is the one at `block` is a trivial
+    /// forwarding wrapper (e.g. `fn cas(&self, ..) { self.0.compare_exchange(..) }`):
+    /// few statements, a single call, and (to avoid ever climbing forever)
+    /// not itself the atomic it forwards to.
+    fn is_trivial_wrapper(body: &Body<'tcx>, block: rustc_middle::mir::BasicBlock) -> bool {
+        if body.basic_blocks.len() > MAX_WRAPPER_BLOCKS {
+            return false;
+        }
+        let statement_count: usize = body.basic_blocks.iter().map(|bb| bb.statements.len()).sum();
+        if statement_count > MAX_WRAPPER_STATEMENTS {
+            return false;
+        }
+        let call_count = body
+            .basic_blocks
+            .iter()
+            .filter(|bb| matches!(bb.terminator().kind, TerminatorKind::Call { .. }))
+            .count();
+        if call_count != 1 || !body.basic_blocks.indices().any(|b| b == block) {
+            return false;
+        }
+        let TerminatorKind::Call { func, .. } = &body[block].terminator().kind else {
+            return false;
+        };
+        !func.const_fn_def().is_some_and(|(def_id, _)| def_id == body.source.def_id())
+    }
+
+    /// Whether `block` lies on a loop in `body`'s control-flow graph: there
+    /// is a back-edge (a terminator-successor edge whose target dominates
+    /// its source) whose natural loop body contains `block`. This is how a
+    /// `compare_exchange_weak`'s retry shows up structurally — the call
+    /// sits inside a `while ... .is_err() { }` (or equivalent `loop`).
+    ///
+    /// `pub` so `detector::atomic` can reuse the same loop-membership check
+    /// for a strong `compare_exchange` instead of duplicating it.
+    pub fn is_block_in_cycle(body: &Body<'tcx>, block: rustc_middle::mir::BasicBlock) -> bool {
+        let dominators = body.basic_blocks.dominators();
+        for (latch, data) in body.basic_blocks.iter_enumerated() {
+            for header in data.terminator().successors() {
+                if dominators.dominates(header, latch)
+                    && Self::natural_loop_contains(body, header, latch, block)
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// The natural loop for back-edge `latch -> header`: every block that
+    /// can reach `latch` by walking predecessors without leaving the loop,
+    /// i.e. without going past `header`. `target` is in the loop if it's
+    /// `header` itself or is found during that backward walk.
+    fn natural_loop_contains(
+        body: &Body<'tcx>,
+        header: rustc_middle::mir::BasicBlock,
+        latch: rustc_middle::mir::BasicBlock,
+        target: rustc_middle::mir::BasicBlock,
+    ) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        seen.insert(header);
+        let mut worklist = vec![latch];
+        while let Some(bb) = worklist.pop() {
+            if !seen.insert(bb) {
+                continue;
+            }
+            for pred in body.basic_blocks.predecessors()[bb].iter() {
+                worklist.push(*pred);
+            }
+        }
+        seen.contains(&target)
+    }
+
+    /// If `place`'s base local is one of `wrapper_body`'s parameters,
+    /// rewrite it in terms of the actual argument passed for that
+    /// parameter at the wrapper's own call site (appending `place`'s own
+    /// projection, e.g. the `.0` field projection of `self.0.compare_exchange(..)`).
+    /// Returns `None` for a place that isn't just forwarding a parameter
+    /// (a value the wrapper computed itself) or where the outer argument
+    /// is a constant rather than a place.
+    fn substitute_through_wrapper(
+        &self,
+        wrapper_body: &Body<'tcx>,
+        place: Place<'tcx>,
+        outer_args: &[Operand<'tcx>],
+    ) -> Option<Place<'tcx>> {
+        let param_index = wrapper_body.args_iter().position(|local| local == place.local)?;
+        let outer_place = outer_args.get(param_index)?.place()?;
+        Some(outer_place.project_deeper(&place.projection, self.tcx))
+    }
+
+    /// Resolve every callsite of the atomic, climbing through any directly
+    /// calling trivial wrapper (bounded by `MAX_INLINE_DEPTH`) so the
+    /// atomic's arguments are read from the outermost frame that actually
+    /// surrounds the logical atomic use rather than from a wrapper's
+    /// otherwise-empty body.
+    fn resolve_callsites(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+    ) -> Vec<(InstanceId, Vec<Operand<'tcx>>, Place<'tcx>, Span, rustc_middle::mir::BasicBlock)> {
+        let mut resolved = Vec::new();
+        let direct_callers: Vec<InstanceId> =
+            callgraph.graph.neighbors_directed(self.instance_id, Incoming).collect();
+        for caller in direct_callers {
+            let inst = callgraph.index_to_instance(caller).unwrap();
+            let body = self.tcx.instance_mir(inst.instance().def);
+            let Some(callsites) = atomic::callsite_locations(callgraph, caller, self.instance_id) else {
+                continue;
+            };
+            for callsite in callsites {
+                if let TerminatorKind::Call { args, destination, fn_span, .. } =
+                    &body[callsite.block].terminator().kind
+                {
+                    self.climb_wrapper_chain(
+                        callgraph,
+                        caller,
+                        body,
+                        callsite.block,
+                        args.clone(),
+                        *destination,
+                        *fn_span,
+                        0,
+                        &mut resolved,
+                    );
+                }
+            }
+        }
+        resolved
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn climb_wrapper_chain(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        instance_id: InstanceId,
+        body: &Body<'tcx>,
+        block: rustc_middle::mir::BasicBlock,
+        args: Vec<Operand<'tcx>>,
+        destination: Place<'tcx>,
+        fn_span: Span,
+        depth: usize,
+        resolved: &mut Vec<(InstanceId, Vec<Operand<'tcx>>, Place<'tcx>, Span, rustc_middle::mir::BasicBlock)>,
+    ) {
+        if depth < MAX_INLINE_DEPTH && Self::is_trivial_wrapper(body, block) {
+            let grandcallers: Vec<InstanceId> =
+                callgraph.graph.neighbors_directed(instance_id, Incoming).collect();
+            if !grandcallers.is_empty() {
+                for grandcaller in grandcallers {
+                    let ginst = callgraph.index_to_instance(grandcaller).unwrap();
+                    let gbody = self.tcx.instance_mir(ginst.instance().def);
+                    let Some(callsites) = atomic::callsite_locations(callgraph, grandcaller, instance_id)
+                    else {
+                        continue;
+                    };
+                    for callsite in callsites {
+                        if let TerminatorKind::Call { args: outer_args, .. } =
+                            &gbody[callsite.block].terminator().kind
+                        {
+                            let substituted_args: Vec<Operand<'tcx>> = args
+                                .iter()
+                                .map(|arg| match arg.place() {
+                                    Some(place) => self
+                                        .substitute_through_wrapper(body, place, outer_args)
+                                        .map(Operand::Move)
+                                        .unwrap_or_else(|| arg.clone()),
+                                    None => arg.clone(),
+                                })
+                                .collect();
+                            let substituted_destination = self
+                                .substitute_through_wrapper(body, destination, outer_args)
+                                .unwrap_or(destination);
+                            self.climb_wrapper_chain(
+                                callgraph,
+                                grandcaller,
+                                gbody,
+                                callsite.block,
+                                substituted_args,
+                                substituted_destination,
+                                fn_span,
+                                depth + 1,
+                                resolved,
+                            );
+                        }
+                    }
+                }
+                return;
+            }
+        }
+        resolved.push((instance_id, args, destination, fn_span, block));
+    }
+
     pub fn analyze(&mut self, callgraph: &CallGraph<'tcx>){
-        let callers: Vec<InstanceId> = callgraph.graph.neighbors_directed(self.instance_id, Incoming).collect();
-        for caller in callers {
+        for (caller, args, destination, fn_span, block) in self.resolve_callsites(callgraph) {
             let instance_node = callgraph.index_to_instance(caller).unwrap();
-            let caller_instance = instance_node.instance();           
-            let body = self.tcx.instance_mir(caller_instance.def); 
-            let callsites = atomic::callsite_locations(callgraph, caller, self.instance_id);
-            for callsite in callsites.unwrap() {
-                if let TerminatorKind::Call {
-                    func: _func,
-                    args,
-                    destination,
-                    fn_span,
-                    ..
-                } = &body[callsite.block].terminator().kind
-                {   
+            let caller_instance = instance_node.instance();
+            let body = self.tcx.instance_mir(caller_instance.def);
+            {
                     let atomic_operate: Option<AtomicInstructions> = AtomicInstructions::from_instance(*self.instance, self.tcx);
+                    let path = self.tcx.def_path_str_with_substs(self.instance.def_id(), self.instance.substs);
+                    let backing = atomic_backing(self.tcx, &path, *self.instance);
                     let mut ordering_type = Vec::new();
                     let mut atomic_arg: Option<Place<'tcx>> = None;
                     let mut influence_value: Vec<Place<'tcx>> = Vec::new();
-                    let source_info = self.tcx.sess.source_map().span_to_diagnostic_string(*fn_span);
+                    let source_info = self.tcx.sess.source_map().span_to_diagnostic_string(fn_span);
                     if source_info.contains(".cargo") {
                         continue;
                     }
+                    let span = fn_span;
+                    // `compare_exchange_weak` may fail spuriously even when the
+                    // comparison would have succeeded, so it's only sound inside
+                    // a loop that retries on `Err`; anywhere else it silently
+                    // drops legitimate updates. Non-weak ops never need this.
+                    let mut in_retry_loop = true;
+                    let mut is_conditional_store = false;
+                    let mut expected_value: Option<Place<'tcx>> = None;
+                    let mut cas_form: Option<CasForm> = None;
                     if let Some(operate) = atomic_operate {
                         match operate {
+                            AtomicInstructions::CompareExchange if path.ends_with("fetch_update") => {
+                                // _9 = AtomicUsize::fetch_update(move _10, move _11, move _12) -> bb3;
+                                // No discrete "new" value to track: `fetch_update`'s
+                                // replacement comes from the retry closure in
+                                // args[2], not a place we can point-to/data-dep on.
+                                atomic_arg = args.get(0).unwrap().place();
+
+                                influence_value.push(destination);
+
+                                let set_ordering_place = args.get(1).and_then(|arg1| arg1.place()).unwrap();
+                                ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &set_ordering_place));
+                                let fetch_ordering_place = args.get(2).and_then(|arg1| arg1.place()).unwrap();
+                                ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &fetch_ordering_place));
+                            },
                             AtomicInstructions::CompareExchange => {
                                 // _14 = AtomicPtr::<Waiter>::compare_exchange(move _15, move _16, move _17, move _20, move _21) -> bb7;
                                 atomic_arg = args.get(0).unwrap().place();
 
-                                influence_value.push(*destination);
+                                influence_value.push(destination);
+
+                                is_conditional_store = true;
+                                expected_value = args.get(1).and_then(|arg1| arg1.place());
 
                                 let write_value = args.get(2).and_then(|arg1| arg1.place());
                                 if let Some(place) = write_value {
@@ -187,28 +573,25 @@ impl<'a, 'tcx> AtomicCollector<'a, 'tcx>{
                                 }
 
                                 let succ_ordering_place = args.get(3).and_then(|arg1| arg1.place()).unwrap();
-                                if let Some(succ_ordering) = AtomicOrd::from_ordering(&body[callsite.block], &succ_ordering_place) {
-                                    ordering_type.push(succ_ordering);
-                                }
-                                // ordering_type.push(AtomicOrd::from_ordering(&body[callsite.block], &succ_ordering_place));
+                                ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &succ_ordering_place));
                                 let fail_ordering_place = args.get(4).and_then(|arg1| arg1.place()).unwrap();
-                                if let Some(fail_ordering) = AtomicOrd::from_ordering(&body[callsite.block], &fail_ordering_place) {
-                                    ordering_type.push(fail_ordering);
+                                ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &fail_ordering_place));
+
+                                if path.ends_with("compare_exchange_weak") {
+                                    in_retry_loop = Self::is_block_in_cycle(body, block);
+                                    cas_form = Some(CasForm::Weak);
+                                } else {
+                                    cas_form = Some(CasForm::Strong);
                                 }
-                                
-                                // ordering_type.push(AtomicOrd::from_ordering(&body[callsite.block], &fail_ordering_place));
                             },
                             AtomicInstructions::Load => {
                                 // _3 = AtomicPtr::<Waiter>::load(move _4, move _5) -> bb1;
                                 atomic_arg = args.get(0).unwrap().place();
 
-                                influence_value.push(*destination);
+                                influence_value.push(destination);
 
                                 let ordering_place = args.get(1).and_then(|arg1| arg1.place()).unwrap();
-                                if let Some(ordering) = AtomicOrd::from_ordering(&body[callsite.block], &ordering_place) {
-                                    ordering_type.push(ordering);
-                                }
-                                // ordering_type.push(AtomicOrd::from_ordering(&body[callsite.block], &ordering_place))
+                                ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &ordering_place));
                             },
                             AtomicInstructions::Store => {
                                 // _36 = AtomicBool::store(move _37, const true, move _38) -> [return: bb10, unwind: bb13];
@@ -220,10 +603,7 @@ impl<'a, 'tcx> AtomicCollector<'a, 'tcx>{
                                 }
 
                                 let ordering_place = args.get(2).and_then(|arg1| arg1.place()).unwrap();
-                                if let Some(ordering) = AtomicOrd::from_ordering(&body[callsite.block], &ordering_place) {
-                                    ordering_type.push(ordering);
-                                }
-                                // ordering_type.push(AtomicOrd::from_ordering(&body[callsite.block], &ordering_place))
+                                ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &ordering_place));
                             },
                             AtomicInstructions::ReadModifyWrite => {
                                 // _2 = AtomicPtr::<Waiter>::swap(move _3, move _4, move _5) -> bb1;
@@ -231,21 +611,21 @@ impl<'a, 'tcx> AtomicCollector<'a, 'tcx>{
                                 if path.ends_with("compare_and_swap") {
                                     atomic_arg = args.get(0).unwrap().place();
 
-                                    influence_value.push(*destination);
+                                    influence_value.push(destination);
+
+                                    is_conditional_store = true;
+                                    expected_value = args.get(1).and_then(|arg1| arg1.place());
 
                                     let write_value = args.get(2).and_then(|arg1| arg1.place());
                                     if let Some(place) = write_value {
                                         influence_value.push(place);
                                     }
                                     let ordering_place = args.get(3).and_then(|arg1| arg1.place()).unwrap();
-                                    if let Some(ordering) = AtomicOrd::from_ordering(&body[callsite.block], &ordering_place) {
-                                        ordering_type.push(ordering);
-                                    }
-                                    // ordering_type.push(AtomicOrd::from_ordering(&body[callsite.block], &ordering_place))
+                                    ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &ordering_place));
                                 } else {
                                     atomic_arg = args.get(0).unwrap().place();
 
-                                    influence_value.push(*destination);
+                                    influence_value.push(destination);
 
                                     let write_value = args.get(1).and_then(|arg1| arg1.place());
                                     if let Some(place) = write_value {
@@ -253,10 +633,7 @@ impl<'a, 'tcx> AtomicCollector<'a, 'tcx>{
                                     }
 
                                     let ordering_place = args.get(2).and_then(|arg1| arg1.place()).unwrap();
-                                    if let Some(ordering) = AtomicOrd::from_ordering(&body[callsite.block], &ordering_place) {
-                                        ordering_type.push(ordering);
-                                    }
-                                    // ordering_type.push(AtomicOrd::from_ordering(&body[callsite.block], &ordering_place))
+                                    ordering_type.push(AtomicOrd::from_ordering(self.tcx, callgraph, caller, body, &ordering_place));
                                 }
                             },
                         }
@@ -264,17 +641,22 @@ impl<'a, 'tcx> AtomicCollector<'a, 'tcx>{
                     // If the ordering_type is null, the memory ordering of this atomic operation is specified by the user,
                     // ignoring the analysis of this atomic operation
                     if !ordering_type.is_empty() {
-                        let atomic_collertor = 
+                        let atomic_collertor =
                         AtomicInfo::new(atomic_arg
                             ,influence_value
                             ,atomic_operate
                             ,caller
                             ,ordering_type
                             ,source_info
+                            ,span
+                            ,backing
+                            ,in_retry_loop
+                            ,is_conditional_store
+                            ,expected_value
+                            ,cas_form
                         );
                         self.atomics.push(atomic_collertor);
                     }
-                }
             }
         }
     }
@@ -288,16 +670,49 @@ pub struct AtomicInfo<'tcx> {
     pub caller_instance: InstanceId,
     pub ordering: Vec<AtomicOrd>,
     pub source_info: String,
+    /// Span of the atomic call site, kept around so detectors can turn a
+    /// finding into a byte-offset fix suggestion via `SourceMap`.
+    pub span: Span,
+    /// Whether this operation is a native lock-free instruction or a
+    /// third-party wrapper's spinlock; see [`AtomicBacking`].
+    pub backing: AtomicBacking,
+    /// For a `compare_exchange_weak`, whether its call site lies on a loop
+    /// in the caller's CFG (so a spurious failure gets retried); always
+    /// `true` for every other operation, since only weak CAS can fail
+    /// spuriously in the first place.
+    pub in_retry_loop: bool,
+    /// Whether `atomic_value[1]` is a conditional store: true for
+    /// `compare_exchange[_weak]` and the legacy `compare_and_swap`, where
+    /// the write only takes effect if the comparison against
+    /// `expected_value` succeeds. `false` for `fetch_update` (no discrete
+    /// "new" value — it's produced by a retry closure) and for every
+    /// unconditional RMW op (`swap`, `fetch_add`, ...).
+    pub is_conditional_store: bool,
+    /// For a conditional store, the "current"/expected value it was
+    /// compared against — i.e. the failure-path read, distinct from
+    /// `atomic_value[1]`'s success-path write. `None` when
+    /// `is_conditional_store` is `false`.
+    pub expected_value: Option<Place<'tcx>>,
+    /// For a plain `compare_exchange[_weak]` call, which form it used; see
+    /// [`CasForm`]. `None` for every other operation, `fetch_update`
+    /// included.
+    pub cas_form: Option<CasForm>,
 }
 
 impl<'tcx> AtomicInfo<'tcx> {
     pub fn new(
-        atomic_place: Option<Place<'tcx>>, 
-        atomic_value: Vec<Place<'tcx>>, 
-        atomic_operate: Option<AtomicInstructions>, 
+        atomic_place: Option<Place<'tcx>>,
+        atomic_value: Vec<Place<'tcx>>,
+        atomic_operate: Option<AtomicInstructions>,
         caller_instance: InstanceId,
         ordering: Vec<AtomicOrd>,
-        source_info: String
+        source_info: String,
+        span: Span,
+        backing: AtomicBacking,
+        in_retry_loop: bool,
+        is_conditional_store: bool,
+        expected_value: Option<Place<'tcx>>,
+        cas_form: Option<CasForm>,
     ) -> Self {
         Self {
             atomic_place,
@@ -306,10 +721,16 @@ impl<'tcx> AtomicInfo<'tcx> {
             caller_instance,
             ordering,
             source_info,
+            span,
+            backing,
+            in_retry_loop,
+            is_conditional_store,
+            expected_value,
+            cas_form,
         }
     }
 
-   
+
 }
 
 #[derive(Clone)]
@@ -352,17 +773,20 @@ impl<'tcx> AtomPart<'tcx> {
         return false;
     }
 
-    pub fn classify_atomic(&self, callgraph: &CallGraph<'tcx>) -> HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>> {
+    pub fn classify_atomic(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        alias_analysis: &AliasAnalysis<'tcx>,
+    ) -> HashMap<String, Vec<(AtomicInfo<'tcx>, Vec<Local>)>> {
         let mut parts_map: HashMap<String, Vec<(AtomicInfo<'_>, Vec<Local>)>> = HashMap::new(); // rustc_middle::mir::ProjectionElem<Local, ty::Ty<'_>>  rustc_middle::mir::ProjectionElem<Local, ty::Ty<'tcx>>
-    
+
         for (atomic, interival) in &self.partner {
             let inst = callgraph.index_to_instance(atomic.caller_instance);
-            let body = self.tcx.instance_mir(inst.unwrap().instance().def); 
+            let body = self.tcx.instance_mir(inst.unwrap().instance().def);
             let atomic_alias = AliasId {
                 instance_id: self.atom_info.caller_instance,
                 local: self.atom_info.atomic_place.unwrap().local,
             };
-            let mut alias_analysis = AliasAnalysis::new(self.tcx); 
             let node = ConstraintNode::Place(Place::from(atomic_alias.local).as_ref());
             let points_to_map = alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
             if let Some(ptses) = points_to_map.get(&node) {
@@ -432,19 +856,26 @@ impl<'tcx> AtomPart<'tcx> {
                 // 保存 index 前后 30% 的 local
                 let start = if index >= range { index - range } else { 0 };
                 let end = usize::min(index + range, total);
-                interim_val.extend(vars_and_temps[start..end-1].iter().copied());
+                // For a conditional store, the expected/"current" value is
+                // only ever read on the failure path, so don't let it
+                // pollute the success-branch association set.
+                interim_val.extend(
+                    vars_and_temps[start..end-1]
+                        .iter()
+                        .copied()
+                        .filter(|local| self.atom_info.expected_value.map_or(true, |p| *local != p.local)),
+                );
             },
         }
         self.partner.push((self.atom_info.clone(), interim_val));
     }
 
 
-    pub fn infer_atomptr_interival(&mut self, callgraph: &CallGraph<'tcx>) {
+    pub fn infer_atomptr_interival(&mut self, callgraph: &CallGraph<'tcx>, alias_analysis: &AliasAnalysis<'tcx>) {
         let inst = callgraph.index_to_instance(self.atom_info.caller_instance);
-        let body = self.tcx.instance_mir(inst.unwrap().instance().def); 
+        let body = self.tcx.instance_mir(inst.unwrap().instance().def);
         let mut interim_val = Vec::new();
-    
-        let mut alias_analysis = AliasAnalysis::new(self.tcx); 
+
         // Get data dependency
         let data_deps = datadep::data_deps(body);
         
@@ -455,28 +886,33 @@ impl<'tcx> AtomPart<'tcx> {
                 let atomic_pts = points_to_map.get(&atomic_node).unwrap();
                 interim_val = atomic_pts.iter().filter_map(|atomic_node| {
                     match atomic_node {
-                        ConstraintNode::Alloc(place) | ConstraintNode::Place(place) 
+                        ConstraintNode::Alloc(place) | ConstraintNode::Place(place)
                             if place.local.index() < self.atom_info.atomic_value[1].local.index()
-                            && place.local != self.atom_info.atomic_value[0].local => {
+                            && place.local != self.atom_info.atomic_value[0].local
+                            // The expected/"current" value is only read on the
+                            // failure path; exclude it so it doesn't pollute
+                            // the success-branch (conditional store) association set.
+                            && self.atom_info.expected_value.map_or(true, |p| place.local != p.local) => {
                                 Some(place.local)
                         },
                         _ => None,
                     }
                 }).collect();
                 interim_val.push(self.atom_info.atomic_value[1].local);
-                
+
                 // Collect the load semantic association variable of atomicptr
                 let local = self.atom_info.atomic_value[0].local;
                 interim_val.push(local.clone());
                 // Obtain the data flow of a specific local
                 let deps = datadep::all_data_dep_on(local, &data_deps, callgraph, self.atom_info.caller_instance, body, self.tcx);
                 for dep in deps {
-                    if dep.index() > self.atom_info.atomic_value[0].local.index() 
-                        && dep != self.atom_info.atomic_value[1].local {
+                    if dep.index() > self.atom_info.atomic_value[0].local.index()
+                        && dep != self.atom_info.atomic_value[1].local
+                        && self.atom_info.expected_value.map_or(true, |p| dep != p.local) {
                         interim_val.push(dep);
                     }
                 }
-                
+
             },
             AtomicInstructions::Load => {
                 let local = self.atom_info.atomic_value[0].local;
@@ -511,13 +947,17 @@ impl<'tcx> AtomPart<'tcx> {
                 let points_to_map = alias_analysis.get_or_insert_pts(inst.unwrap().instance().def_id(), body).clone();
                 let atomic_node = ConstraintNode::Place(Place::from(self.atom_info.atomic_value[1].local).as_ref());
                 let atomic_pts = points_to_map.get(&atomic_node);
-                if let Some(atomic_pts) = atomic_pts {                 
+                if let Some(atomic_pts) = atomic_pts {
                     // Collect the store semantic association variable of atomicptr
                     interim_val = atomic_pts.iter().filter_map(|atomic_node| {
                         match atomic_node {
-                            ConstraintNode::Alloc(place) | ConstraintNode::Place(place) 
+                            ConstraintNode::Alloc(place) | ConstraintNode::Place(place)
                                 if place.local.index() < self.atom_info.atomic_value[1].local.index()
-                                    && place.local != self.atom_info.atomic_value[0].local => {
+                                    && place.local != self.atom_info.atomic_value[0].local
+                                    // Legacy `compare_and_swap` is classified as
+                                    // `ReadModifyWrite` too; exclude its expected
+                                    // value the same way the real CAS arm does.
+                                    && self.atom_info.expected_value.map_or(true, |p| place.local != p.local) => {
                                     Some(place.local)
                             },
                             _ => None,
@@ -534,8 +974,9 @@ impl<'tcx> AtomPart<'tcx> {
                 // Obtain the data flow of a specific local
                 let deps = datadep::all_data_dep_on(local, &data_deps, callgraph, self.atom_info.caller_instance, body, self.tcx);
                 for dep in deps {
-                    if dep.index() > self.atom_info.atomic_value[0].local.index() 
-                        && dep != self.atom_info.atomic_value[1].local {
+                    if dep.index() > self.atom_info.atomic_value[0].local.index()
+                        && dep != self.atom_info.atomic_value[1].local
+                        && self.atom_info.expected_value.map_or(true, |p| dep != p.local) {
                         interim_val.push(dep);
                     }
                 }
@@ -0,0 +1,49 @@
+//! Recognize `tokio::sync::broadcast` channel-API call sites, so
+//! `detector::broadcast::SlowReceiverDetector` can reason about multiple
+//! receivers sharing one bounded ring buffer -- the same role
+//! `interest::concurrency::chan::ChanApi` plays for `std::sync::mpsc`.
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BroadcastApi {
+    /// `broadcast::channel(capacity)` -- creates the ring buffer and its
+    /// first `Receiver`.
+    Create,
+    /// `Sender::<T>::subscribe()` -- creates another `Receiver` sharing the
+    /// same ring buffer and capacity as every other one subscribed from the
+    /// same (or a cloned) `Sender`.
+    Subscribe,
+    Send,
+    /// `Receiver::<T>::recv()`/`try_recv()`. Whether this call site actually
+    /// handles the `Lagged`/`Closed` variants of its `Result` is a separate
+    /// question, answered by `SlowReceiverDetector::handles_lag_or_closed`
+    /// rather than by this enum -- this only records that the call is a
+    /// receive.
+    Recv,
+}
+
+impl BroadcastApi {
+    pub fn from_def_id(def_id: DefId, tcx: TyCtxt<'_>) -> Option<Self> {
+        let path = tcx.def_path_str(def_id);
+        let prefix = "tokio::sync::broadcast::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"channel") {
+            Some(BroadcastApi::Create)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"subscribe") {
+            Some(BroadcastApi::Subscribe)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"send") {
+            Some(BroadcastApi::Send)
+        } else if tail.starts_with(b"Receiver::") && (tail.ends_with(b"recv") || tail.ends_with(b"try_recv")) {
+            Some(BroadcastApi::Recv)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Recognize channel-API call sites across the ecosystem's most common
+//! channel crates -- `std::sync::mpsc`, `crossbeam_channel`,
+//! `tokio::sync::mpsc`, and `futures::channel::mpsc` -- so
+//! `detector::chan::ChannelDeadlockDetector` can pair them with
+//! `analysis::controldep`'s control-dependence graph.
+//!
+//! Only the blocking create/send/recv shapes each crate shares with
+//! `std::sync::mpsc` are modeled; a crate's non-blocking variants
+//! (`try_send`, `try_recv`, `send_timeout`, crossbeam's `select!`) aren't
+//! recognized at all here, the same "don't guess at a different blocking
+//! shape" stance the rest of this matcher already takes.
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChanApi {
+    /// An unbounded channel constructor (`std::sync::mpsc::channel`,
+    /// `crossbeam_channel::unbounded`, `tokio::sync::mpsc::unbounded_channel`,
+    /// `futures::channel::mpsc::unbounded`) -- so only a `recv()` on it can
+    /// ever block; a `send()` always succeeds immediately.
+    Create,
+    /// A bounded channel constructor (`std::sync::mpsc::sync_channel`,
+    /// `crossbeam_channel::bounded`, `tokio::sync::mpsc::channel`,
+    /// `futures::channel::mpsc::channel`) -- unlike `Create`, a `send()`
+    /// here can itself block once the channel is full, not just `recv()`.
+    /// The bound isn't evaluated here, so every bounded channel is
+    /// conservatively treated as able to block a `send()` regardless of
+    /// its actual capacity -- the same "flag the pattern, don't prove it"
+    /// stance this crate already takes on
+    /// `AtomicityViolationDetector::detect_aba_hazard`.
+    CreateBounded,
+    Send,
+    Recv,
+}
+
+impl ChanApi {
+    pub fn from_def_id(def_id: DefId, tcx: TyCtxt<'_>) -> Option<Self> {
+        let path = tcx.def_path_str(def_id);
+        // `try_send`/`try_recv` end in the same suffixes the matchers below
+        // test for, so rule them out first.
+        if path.ends_with("try_send") || path.ends_with("try_recv") {
+            return None;
+        }
+        Self::from_std(&path)
+            .or_else(|| Self::from_crossbeam(&path))
+            .or_else(|| Self::from_tokio(&path))
+            .or_else(|| Self::from_futures(&path))
+    }
+
+    fn from_std(path: &str) -> Option<Self> {
+        let prefix = "std::sync::mpsc::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"sync_channel") {
+            Some(ChanApi::CreateBounded)
+        } else if tail.starts_with(b"channel") {
+            Some(ChanApi::Create)
+        } else if (tail.starts_with(b"Sender::") || tail.starts_with(b"SyncSender::")) && tail.ends_with(b"send") {
+            Some(ChanApi::Send)
+        } else if tail.starts_with(b"Receiver::") && tail.ends_with(b"recv") {
+            Some(ChanApi::Recv)
+        } else {
+            None
+        }
+    }
+
+    fn from_crossbeam(path: &str) -> Option<Self> {
+        let prefix = "crossbeam_channel::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"unbounded") {
+            Some(ChanApi::Create)
+        } else if tail.starts_with(b"bounded") {
+            Some(ChanApi::CreateBounded)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"send") {
+            Some(ChanApi::Send)
+        } else if tail.starts_with(b"Receiver::") && tail.ends_with(b"recv") {
+            Some(ChanApi::Recv)
+        } else {
+            None
+        }
+    }
+
+    fn from_tokio(path: &str) -> Option<Self> {
+        let prefix = "tokio::sync::mpsc::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"unbounded_channel") {
+            Some(ChanApi::Create)
+        } else if tail.starts_with(b"channel") {
+            Some(ChanApi::CreateBounded)
+        } else if tail.starts_with(b"Sender::") && (tail.ends_with(b"send") || tail.ends_with(b"blocking_send")) {
+            Some(ChanApi::Send)
+        } else if (tail.starts_with(b"Receiver::") || tail.starts_with(b"UnboundedReceiver::")) && tail.ends_with(b"recv") {
+            Some(ChanApi::Recv)
+        } else {
+            None
+        }
+    }
+
+    /// Only construction and the send side are matched: `Receiver` is
+    /// consumed through the generic `Stream`/`StreamExt` trait
+    /// (`.next()`/`.try_next()`), not a dedicated `recv` method, so
+    /// there's no stable per-crate `def_path_str` to match the way there
+    /// is for the other three families.
+    fn from_futures(path: &str) -> Option<Self> {
+        // `futures::channel::mpsc` re-exports `futures_channel::mpsc`, and
+        // which path rustc reports depends on which the caller named.
+        let Some(tail) = path
+            .strip_prefix("futures_channel::mpsc::")
+            .or_else(|| path.strip_prefix("futures::channel::mpsc::"))
+        else {
+            return None;
+        };
+        let tail = tail.as_bytes();
+        if tail.starts_with(b"unbounded") {
+            Some(ChanApi::Create)
+        } else if tail.starts_with(b"channel") {
+            Some(ChanApi::CreateBounded)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"send") {
+            Some(ChanApi::Send)
+        } else {
+            None
+        }
+    }
+}
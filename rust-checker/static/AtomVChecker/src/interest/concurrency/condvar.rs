@@ -0,0 +1,63 @@
+//! Recognize `std::sync::Condvar`/`Mutex`/`RwLock` call sites needed by
+//! `detector::condvar::CondvarDetector`: a `wait`/`wait_timeout` call (to
+//! check it's guarded by a predicate-recheck loop), and a lock acquisition
+//! (to build the crate-wide lock-order graph).
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CondvarApi {
+    /// `Condvar::wait`/`wait_timeout` -- takes a `MutexGuard` and hands one
+    /// back with no predicate of its own, so soundness depends entirely on
+    /// the caller re-checking a shared condition on every wakeup.
+    Wait,
+    /// `Condvar::wait_while`/`wait_timeout_while` -- the re-check predicate
+    /// is a closure argument baked into the call itself, so unlike `Wait`
+    /// there's no separate loop for `CondvarDetector` to look for.
+    WaitWhile,
+    NotifyOne,
+    NotifyAll,
+}
+
+impl CondvarApi {
+    pub fn from_def_id(def_id: DefId, tcx: TyCtxt<'_>) -> Option<Self> {
+        let path = tcx.def_path_str(def_id);
+        let prefix = "std::sync::Condvar::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"wait_while") || tail.starts_with(b"wait_timeout_while") {
+            Some(CondvarApi::WaitWhile)
+        } else if tail.starts_with(b"wait") {
+            Some(CondvarApi::Wait)
+        } else if tail.starts_with(b"notify_one") {
+            Some(CondvarApi::NotifyOne)
+        } else if tail.starts_with(b"notify_all") {
+            Some(CondvarApi::NotifyAll)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` (a monomorphized call path, as rendered by
+/// `def_path_str_with_substs`) is a `Mutex`/`RwLock` acquisition --
+/// `lock`/`read`/`write` -- and if so, the key `CondvarDetector`'s
+/// lock-order graph should use for it: the receiver type on its own,
+/// without the method name. Keying on the monomorphized receiver type
+/// rather than any one call site collapses distinct same-typed mutexes
+/// onto one graph node -- an accepted over-approximation, the same
+/// trade-off `atomic_backing` already makes by keying atomic wrappers on
+/// a rendered type path rather than pointer identity.
+pub fn lock_acquire_key(path: &str) -> Option<String> {
+    let is_acquire = (path.starts_with("std::sync::Mutex::") || path.starts_with("std::sync::RwLock::"))
+        && (path.ends_with("::lock") || path.ends_with("::read") || path.ends_with("::write"));
+    if !is_acquire {
+        return None;
+    }
+    path.rsplit_once("::").map(|(receiver, _method)| receiver.to_owned())
+}
@@ -0,0 +1,115 @@
+//! Project-model layer for non-Cargo targets (Buck, Bazel, kernel modules,
+//! ...), analogous to rust-analyzer's `ProjectWorkspace::{Cargo, Json}`
+//! split. `cargo atomvchecker` drives analysis by having `cargo build`
+//! invoke our `RUSTC_WRAPPER` for every crate it already knows about; this
+//! module instead reads a `rust-project.json` describing the crate graph
+//! directly and turns each entry into the `rustc` invocation that
+//! `atomvchecker-json` needs to compile (and thereby analyze) it. Once
+//! rustc is running with our callbacks installed, `callbacks` builds the
+//! same `CallGraph`/`Body` inputs the atomic-partner analysis consumes
+//! regardless of how the crate's compilation was triggered.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// One `deps[]` entry. `rust-project.json` refers to dependencies by index
+/// into the top-level `crates` array rather than by name, so `name` here
+/// is just what this crate calls it (i.e. what goes on the left of
+/// `--extern name=path` when compiling it).
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonDep {
+    #[serde(rename = "crate")]
+    pub krate: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonCrate {
+    pub display_name: String,
+    pub root_module: PathBuf,
+    pub edition: String,
+    #[serde(default)]
+    pub deps: Vec<JsonDep>,
+    #[serde(default)]
+    pub cfg: Vec<String>,
+    #[serde(default = "default_true")]
+    pub is_workspace_member: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonProject {
+    pub sysroot: Option<PathBuf>,
+    pub crates: Vec<JsonCrate>,
+}
+
+impl JsonProject {
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Order crate indices so every dependency is compiled (and its rlib
+    /// available for `--extern`) before any crate that needs it.
+    pub fn build_order(&self) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut visited = vec![false; self.crates.len()];
+        for idx in 0..self.crates.len() {
+            self.visit(idx, &mut visited, &mut order);
+        }
+        order
+    }
+
+    fn visit(&self, idx: usize, visited: &mut Vec<bool>, order: &mut Vec<usize>) {
+        if visited[idx] {
+            return;
+        }
+        visited[idx] = true;
+        for dep in &self.crates[idx].deps {
+            self.visit(dep.krate, visited, order);
+        }
+        order.push(idx);
+    }
+}
+
+/// Turn one crate entry into the `rustc` flags needed to compile it with
+/// our callbacks installed, given where each already-built dependency's
+/// rlib was written.
+pub fn rustc_args(
+    project: &JsonProject,
+    krate: &JsonCrate,
+    rlib_for: &HashMap<usize, PathBuf>,
+    out_dir: &Path,
+) -> Vec<String> {
+    let mut args = vec![
+        krate.root_module.to_string_lossy().into_owned(),
+        "--crate-name".to_owned(),
+        krate.display_name.clone(),
+        "--edition".to_owned(),
+        krate.edition.clone(),
+        "--crate-type".to_owned(),
+        "lib".to_owned(),
+        "--out-dir".to_owned(),
+        out_dir.to_string_lossy().into_owned(),
+    ];
+    if let Some(sysroot) = &project.sysroot {
+        args.push("--sysroot".to_owned());
+        args.push(sysroot.to_string_lossy().into_owned());
+    }
+    for cfg in &krate.cfg {
+        args.push("--cfg".to_owned());
+        args.push(cfg.clone());
+    }
+    for dep in &krate.deps {
+        if let Some(rlib) = rlib_for.get(&dep.krate) {
+            args.push("--extern".to_owned());
+            args.push(format!("{}={}", dep.name, rlib.display()));
+        }
+    }
+    args
+}
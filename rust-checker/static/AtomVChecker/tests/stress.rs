@@ -0,0 +1,154 @@
+//! Stress/replay harness: runs instrumented concurrency scenarios many
+//! times under perturbed scheduling (see `support::stress`), looking for
+//! a failing interleaving of a bug the static detectors
+//! (`detector::chan`, `detector::condvar`) are designed to catch --
+//! confirming a reported deadlock is real, and reproducibly so, rather
+//! than trusting a static finding on its own.
+mod support;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use support::stress::{run_stress, Recorder, Schedule, SyncEventKind};
+
+fn expect_failure(schedule: Option<Schedule>, scenario_name: &str) -> Schedule {
+    schedule.unwrap_or_else(|| {
+        panic!(
+            "expected {} to fail under at least one of the {} sampled interleavings, but every \
+             run completed cleanly",
+            scenario_name,
+            support::stress::iteration_count(),
+        )
+    })
+}
+
+/// Mirrors the cross-thread channel deadlock
+/// `detector::chan::ChannelDeadlockDetector::detect_cross_thread` flags
+/// statically: the spawned thread's `recv` waits for a `send` the main
+/// thread only reaches after its own `recv`, which in turn waits on the
+/// spawned thread's `send`. Deterministic rather than schedule-dependent
+/// (both sides block in their very first statement), but it's the
+/// simplest possible exercise of the `ChannelSend`/`ChannelRecv`
+/// instrumentation and of `run_stress`'s timeout-based failure signal.
+fn cross_thread_channel_deadlock(recorder: Arc<Recorder>) -> bool {
+    let (to_worker_tx, to_worker_rx) = mpsc::channel::<()>();
+    let (to_main_tx, to_main_rx) = mpsc::channel::<()>();
+
+    let worker_recorder = Arc::clone(&recorder);
+    let worker = std::thread::spawn(move || {
+        Recorder::set_thread_index(1);
+        worker_recorder.record(SyncEventKind::ChannelRecv, "worker recv from main");
+        let _ = to_main_rx.recv();
+        worker_recorder.record(SyncEventKind::ChannelSend, "worker send to main");
+        let _ = to_worker_tx.send(());
+    });
+
+    Recorder::set_thread_index(0);
+    recorder.record(SyncEventKind::ChannelRecv, "main recv from worker");
+    let _ = to_worker_rx.recv();
+    recorder.record(SyncEventKind::ChannelSend, "main send to worker");
+    let _ = to_main_tx.send(());
+
+    worker.join().is_ok()
+}
+
+#[test]
+fn cross_thread_channel_deadlock_reproduces_under_stress() {
+    let schedule = expect_failure(run_stress(Duration::from_millis(200), cross_thread_channel_deadlock), "cross_thread_channel_deadlock");
+    eprintln!("reproduced with seed {}: {:#?}", schedule.seed, schedule.events);
+}
+
+/// Mirrors the lost-wakeup bug
+/// `detector::condvar::CondvarDetector::detect_wait_not_in_loop` flags
+/// statically: the predicate is checked and the `wait` call made in two
+/// separate lock acquisitions, leaving a window between them where
+/// `notifier` can set the flag and `notify_one` before `waiter` ever
+/// starts waiting -- losing the wakeup. Genuinely schedule-dependent:
+/// whether the bug reproduces on a given iteration depends on whether
+/// `Recorder::record`'s perturbation happens to park `waiter` in that
+/// window, which is exactly why this is driven through `run_stress`
+/// rather than run once.
+fn lost_wakeup_race(recorder: Arc<Recorder>) -> bool {
+    let pair = Arc::new((Mutex::new(false), Condvar::new()));
+
+    let pair_for_notifier = Arc::clone(&pair);
+    let notifier_recorder = Arc::clone(&recorder);
+    let notifier = std::thread::spawn(move || {
+        Recorder::set_thread_index(1);
+        let (lock, cvar) = &*pair_for_notifier;
+        let mut ready = lock.lock().unwrap();
+        notifier_recorder.record(SyncEventKind::LockAcquire, "notifier locks to set ready");
+        *ready = true;
+        cvar.notify_one();
+    });
+
+    Recorder::set_thread_index(0);
+    let (lock, cvar) = &*pair;
+    let already_ready = *lock.lock().unwrap();
+    recorder.record(SyncEventKind::LockAcquire, "waiter checks predicate (lock dropped after)");
+    if !already_ready {
+        let guard = lock.lock().unwrap();
+        recorder.record(SyncEventKind::LockAcquire, "waiter re-locks to wait");
+        let _ = cvar.wait(guard).unwrap();
+    }
+
+    notifier.join().is_ok()
+}
+
+#[test]
+fn lost_wakeup_reproduces_under_stress() {
+    let schedule = expect_failure(run_stress(Duration::from_millis(200), lost_wakeup_race), "lost_wakeup_race");
+    eprintln!("reproduced with seed {}: {:#?}", schedule.seed, schedule.events);
+}
+
+/// Exercises `AtomicOp` instrumentation with a check-then-act race on a
+/// plain (non-atomic in intent) flag: `worker` only does its one-time
+/// setup if `initialized` was false, but the read and the store aren't a
+/// single RMW, so both threads can read `false` before either writes
+/// `true`, each concluding it's responsible for initialization. Like
+/// `lost_wakeup_race`, reproducing this depends on the perturbation
+/// parking one thread between the read and the write.
+fn double_init_race(recorder: Arc<Recorder>) -> bool {
+    let initialized = Arc::new(AtomicBool::new(false));
+    let ran_init = Arc::new(AtomicBool::new(false));
+
+    let initialized_for_worker = Arc::clone(&initialized);
+    let ran_init_for_worker = Arc::clone(&ran_init);
+    let worker_recorder = Arc::clone(&recorder);
+    let worker = std::thread::spawn(move || {
+        Recorder::set_thread_index(1);
+        worker_recorder.record(SyncEventKind::AtomicOp, "worker reads initialized");
+        if !initialized_for_worker.load(Ordering::Relaxed) {
+            if ran_init_for_worker.swap(true, Ordering::Relaxed) {
+                // `main` already ran init too -- the race triggered.
+                return false;
+            }
+            worker_recorder.record(SyncEventKind::AtomicOp, "worker writes initialized");
+            initialized_for_worker.store(true, Ordering::Relaxed);
+        }
+        true
+    });
+
+    Recorder::set_thread_index(0);
+    recorder.record(SyncEventKind::AtomicOp, "main reads initialized");
+    let main_ok = if !initialized.load(Ordering::Relaxed) {
+        if ran_init.swap(true, Ordering::Relaxed) {
+            false
+        } else {
+            recorder.record(SyncEventKind::AtomicOp, "main writes initialized");
+            initialized.store(true, Ordering::Relaxed);
+            true
+        }
+    } else {
+        true
+    };
+
+    main_ok && worker.join().unwrap_or(false)
+}
+
+#[test]
+fn double_init_race_reproduces_under_stress() {
+    let schedule = expect_failure(run_stress(Duration::from_millis(200), double_init_race), "double_init_race");
+    eprintln!("reproduced with seed {}: {:#?}", schedule.seed, schedule.events);
+}
@@ -0,0 +1,103 @@
+//! Shared support for the containerized regression harness (see
+//! `../regression.rs`): each fixture names a real crate with a known
+//! concurrency bug, and the harness checks it out and builds it inside a
+//! disposable container so the toolchain and dependency versions are
+//! pinned and reproducible across CI runs.
+use std::process::Command;
+
+use atomvchecker::detector::atomic::diagnostic::Diagnostic;
+use atomvchecker::options::DetectorKind;
+
+pub mod stress;
+
+/// The container image `run_fixture` builds and runs the crate in. Pinned
+/// to a specific rustc nightly so a toolchain bump can't silently change
+/// what a fixture reports.
+const IMAGE: &str = "atomvchecker-regression:pinned";
+
+/// A real crate with a known concurrency bug, and the findings `cargo
+/// atomvchecker` is expected to report against it.
+pub struct Fixture {
+    pub crate_name: &'static str,
+    pub git_url: &'static str,
+    pub git_rev: &'static str,
+    pub detector_kind: DetectorKind,
+    pub expected: &'static [ExpectedFinding],
+}
+
+/// One finding a fixture's crate is known to contain, identified the same
+/// way a human reviewer would point at it in a bug report.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExpectedFinding {
+    pub file: &'static str,
+    pub line: u32,
+    pub kind: &'static str,
+}
+
+/// Build and run `fixture.crate_name` inside a disposable container,
+/// returning the diagnostics `cargo atomvchecker --message-format=json`
+/// printed to stdout.
+pub fn run_fixture(fixture: &Fixture) -> Vec<Diagnostic> {
+    let output = Command::new("docker")
+        .args(["run", "--rm", IMAGE])
+        .arg(fixture.git_url)
+        .arg(fixture.git_rev)
+        .arg("--")
+        .arg("-k")
+        .arg(format!("{:?}", fixture.detector_kind))
+        .arg("--message-format=json")
+        .output()
+        .unwrap_or_else(|e| panic!("failed to spawn container for {}: {}", fixture.crate_name, e));
+    assert!(
+        output.status.success(),
+        "container run for {} exited with {}: {}",
+        fixture.crate_name,
+        output.status,
+        String::from_utf8_lossy(&output.stderr),
+    );
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("bad diagnostic line {:?}: {}", line, e)))
+        .collect()
+}
+
+/// Assert that `got` contains exactly the findings `fixture.expected`
+/// declares, regardless of order: every expected finding was reported, and
+/// nothing else was. A fixture whose crate is genuinely clean declares
+/// `expected: &[]`, so any unexpected finding there fails the "nothing
+/// else" half just as surely as a missing finding fails the "every
+/// expected finding" half.
+pub fn assert_matches_expected(fixture: &Fixture, got: &[Diagnostic]) {
+    let mut remaining: Vec<&ExpectedFinding> = fixture.expected.iter().collect();
+    let mut unexpected: Vec<&Diagnostic> = Vec::new();
+    for diagnostic in got {
+        let Some(span) = diagnostic.spans.iter().find(|s| s.is_primary) else {
+            unexpected.push(diagnostic);
+            continue;
+        };
+        let position = remaining.iter().position(|expected| {
+            span.file_name.ends_with(expected.file)
+                && span.line_start == expected.line
+                && diagnostic.message.contains(expected.kind)
+        });
+        match position {
+            Some(index) => {
+                remaining.remove(index);
+            }
+            None => unexpected.push(diagnostic),
+        }
+    }
+    assert!(
+        remaining.is_empty(),
+        "{}: expected findings not reported: {:?}",
+        fixture.crate_name,
+        remaining,
+    );
+    assert!(
+        unexpected.is_empty(),
+        "{}: unexpected findings reported: {:?}",
+        fixture.crate_name,
+        unexpected,
+    );
+}
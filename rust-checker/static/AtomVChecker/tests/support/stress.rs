@@ -0,0 +1,158 @@
+//! Bounded interleaving exploration for reproducing the scheduling-
+//! dependent bugs `detector::chan`/`detector::condvar` flag statically --
+//! modeled on the standard library's own `mpsc` stress tests (many
+//! iterations of the same racy scenario under varied timing), but
+//! instrumented so a failing run's schedule can be printed and replayed
+//! instead of just rerun and hoped for.
+//!
+//! Exhaustively enumerating interleavings, the way a full DPOR model
+//! checker would, isn't attempted here -- that needs control over when
+//! each thread is allowed to proceed, which isn't reachable from safe,
+//! unmodified `std::sync` primitives. Instead this randomly *samples*
+//! interleavings by perturbing thread timing with a seeded PRNG at each
+//! instrumented sync point, and records the resulting per-thread order of
+//! events so a failing seed's schedule can be inspected. A scenario that
+//! races on real memory ordering rather than lock/channel/condvar
+//! sequencing (a bare relaxed-atomic data race) may still not reproduce
+//! here even so, since most hardware this runs on is far stricter than
+//! Rust's abstract memory model -- that class of bug needs a real model
+//! checker (e.g. loom) with its own emulated atomics, which this harness
+//! doesn't attempt to be.
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many times `run_stress` retries a scenario before giving up
+/// without finding a failing schedule. `cfg(miri)` keeps this small,
+/// since each iteration is already far more expensive under Miri; a
+/// normal `cargo test` run affords a much larger budget, needed to make
+/// sampling a rare interleaving plausible.
+pub fn iteration_count() -> u32 {
+    if cfg!(miri) {
+        50
+    } else {
+        std::env::var("ATOMVCHECKER_STRESS_ITERS").ok().and_then(|v| v.parse().ok()).unwrap_or(10_000)
+    }
+}
+
+/// One recorded synchronization event: which thread it happened on (an
+/// index into the scenario's own thread list, set via
+/// `Recorder::set_thread_index`, not an OS `ThreadId` -- so a printed
+/// schedule doesn't depend on OS thread numbering), what kind of sync op
+/// it was, and the call-site label the scenario passed to `record`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncEvent {
+    pub thread: usize,
+    pub kind: SyncEventKind,
+    pub site: &'static str,
+}
+
+/// The instrumented synchronization point kinds this request names:
+/// channel ops, atomic accesses, lock acquisitions. `Recorder::record`
+/// doesn't interpret these -- they're carried through to the printed
+/// schedule purely for a human reading it back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncEventKind {
+    ChannelSend,
+    ChannelRecv,
+    AtomicOp,
+    LockAcquire,
+}
+
+/// A minimal reproducing interleaving: the ordered sequence of sync
+/// events observed during the one run that failed, plus the seed that
+/// produced it. Passing that seed back into a fresh `Recorder` (see
+/// `run_stress`'s own seeding) reproduces the same perturbation, and so
+/// long as the scenario itself is otherwise deterministic, the same
+/// failure.
+#[derive(Debug)]
+pub struct Schedule {
+    pub seed: u64,
+    pub events: Vec<SyncEvent>,
+}
+
+thread_local! {
+    static THREAD_INDEX: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Recorder shared by every thread in one run of a scenario.
+pub struct Recorder {
+    seed: AtomicU64,
+    events: Mutex<Vec<SyncEvent>>,
+}
+
+impl Recorder {
+    fn new(seed: u64) -> Self {
+        Self { seed: AtomicU64::new(seed), events: Mutex::new(Vec::new()) }
+    }
+
+    /// Tag the calling OS thread's future `record` calls with
+    /// `index`. Call once at the top of each thread a scenario spawns,
+    /// with a distinct index per thread, before that thread's first
+    /// `record` call.
+    pub fn set_thread_index(index: usize) {
+        THREAD_INDEX.with(|cell| cell.set(index));
+    }
+
+    /// Record one instrumented sync event on the calling thread, then
+    /// perturb scheduling: a cheap xorshift step on the run's seed
+    /// decides whether this thread yields here, giving a sibling thread
+    /// a chance to interleave at this exact point. This is the
+    /// "randomly sample interleavings" half of the request -- varying
+    /// which schedule each iteration happens to hit, not enumerating
+    /// all of them.
+    pub fn record(&self, kind: SyncEventKind, site: &'static str) {
+        let thread = THREAD_INDEX.with(|cell| cell.get());
+        self.events.lock().unwrap().push(SyncEvent { thread, kind, site });
+        if self.next_bit() {
+            std::thread::yield_now();
+        }
+    }
+
+    /// xorshift64 -- not cryptographic, just cheap and seed-reproducible,
+    /// which is all a replay needs.
+    fn next_bit(&self) -> bool {
+        let mut x = self.seed.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed.store(x, Ordering::Relaxed);
+        x & 1 == 1
+    }
+}
+
+/// Run `scenario` up to `iteration_count()` times, each against a
+/// freshly seeded `Recorder`, until either `scenario` returns `false` (or
+/// doesn't return within `timeout` -- the harness's only signal that a
+/// run deadlocked) or the budget is exhausted. On the first failing run,
+/// returns the `Schedule` that reproduced it.
+///
+/// `scenario` runs on a plain, deliberately unjoined `std::thread::spawn`
+/// rather than anything this harness can forcibly cancel: if it
+/// deadlocks, that thread (and anything it spawned) leaks for the rest
+/// of the process's life, since there's no safe way to kill a blocked
+/// std thread. `recv_timeout` below is what lets `run_stress` move on to
+/// the next iteration regardless. Run stress tests in their own process
+/// (or accept the leak) if that matters for a long-running suite.
+pub fn run_stress<F>(timeout: Duration, scenario: F) -> Option<Schedule>
+where
+    F: Fn(std::sync::Arc<Recorder>) -> bool + Send + Sync + 'static,
+{
+    let scenario = std::sync::Arc::new(scenario);
+    for iteration in 0..iteration_count() {
+        let seed = 0x9E37_79B9_7F4A_7C15_u64 ^ u64::from(iteration);
+        let recorder = std::sync::Arc::new(Recorder::new(seed));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let scenario_for_thread = std::sync::Arc::clone(&scenario);
+        let recorder_for_thread = std::sync::Arc::clone(&recorder);
+        std::thread::spawn(move || {
+            let _ = tx.send(scenario_for_thread(recorder_for_thread));
+        });
+        let completed_cleanly = rx.recv_timeout(timeout).unwrap_or(false);
+        if !completed_cleanly {
+            return Some(Schedule { seed, events: recorder.events.lock().unwrap().clone() });
+        }
+    }
+    None
+}
@@ -5,10 +5,24 @@ extern crate rustc_span;
 use std::collections::VecDeque;
 
 use petgraph::visit::IntoNodeReferences;
+use petgraph::Direction::{Incoming, Outgoing};
+use rustc_data_structures::graph::dominators::Dominators;
 use rustc_hash::{FxHashMap, FxHashSet};
-use rustc_middle::{mir::Local, ty::{TyCtxt, TypingEnv}};
+use rustc_middle::{
+    mir::{BasicBlock, Local, Location, StatementKind, TerminatorKind},
+    ty::{TyCtxt, TypingEnv},
+};
 
-use crate::{analysis::{callgraph::{CallGraph, InstanceId}, pointsto::AliasAnalysis}, interest::concurrency::chan::ChanApi};
+use crate::{
+    analysis::{
+        callgraph::{CallGraph, InstanceId},
+        pointsto::AliasAnalysis,
+    },
+    interest::{
+        concurrency::chan::{ChanApi, ChanOperation},
+        memory::ownership::is_arc_or_rc_clone,
+    },
+};
 
 pub struct ChanDetector<'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -22,6 +36,7 @@ pub struct ReceiverId {
     pub local: Local,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct SenderId {
     pub instance_id: InstanceId,
     pub local: Local,
@@ -53,6 +68,15 @@ impl LiveReceivers {
     }
 }
 
+/// A guaranteed-blocking send: every receiver that could be bound to
+/// `sender`'s channel is already dead by the time `send_at` runs, so the
+/// send has nothing left that could ever receive it.
+#[derive(Debug, Clone)]
+pub struct BlockingSend {
+    pub sender: SenderId,
+    pub send_at: Location,
+}
+
 impl<'tcx> ChanDetector<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>, typing_env: TypingEnv<'tcx>) -> Self {
         Self {
@@ -75,26 +99,427 @@ impl<'tcx> ChanDetector<'tcx> {
             .collect()
     }
 
-    pub fn detect<'a>(&mut self,
+    /// Every `channel()` call site, with its `Sender`/`Receiver` both bound
+    /// to the (unprojected) destination local. Tuple fields aren't tracked
+    /// separately: `_t = channel()` ties both ends to `_t`, and a place
+    /// whose base local is `_t` (`(_t.0)`, `(_t.1)`, or a later clone/move
+    /// of either) resolves back to this site through `resolve_sender`/
+    /// `resolve_receiver`.
+    fn bind_chan_sites(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        chan_apis: &FxHashMap<InstanceId, ChanApi>,
+    ) -> Vec<(SenderId, ReceiverId)> {
+        let mut sites = Vec::new();
+        for (&callee, chan_api) in chan_apis {
+            if chan_api.operation() != ChanOperation::Create {
+                continue;
+            }
+            let callers: Vec<InstanceId> = callgraph.graph.neighbors_directed(callee, Incoming).collect();
+            for caller in callers {
+                let inst = callgraph.index_to_instance(caller).unwrap();
+                let body = self.tcx.instance_mir(inst.instance().def);
+                let Some(callsites) = callgraph.callsites(caller, callee) else {
+                    continue;
+                };
+                for callsite in callsites {
+                    if let Some(location) = callsite.location() {
+                        if let TerminatorKind::Call { destination, .. } =
+                            &body[location.block].terminator().kind
+                        {
+                            let local = destination.local;
+                            sites.push((
+                                SenderId { instance_id: caller, local },
+                                ReceiverId { instance_id: caller, local },
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        sites
+    }
+
+    /// Call sites of `kind` among `chan_apis`, as `(caller, callsite
+    /// location, the receiver/sender local passed as `self`)`. `kind` is
+    /// matched against `ChanApi::operation()`, so this works the same way
+    /// across every backend `ChanApi` recognizes, not just `std::sync::mpsc`.
+    fn call_sites_of(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        chan_apis: &FxHashMap<InstanceId, ChanApi>,
+        kind: ChanOperation,
+    ) -> Vec<(InstanceId, Location, Local)> {
+        let mut out = Vec::new();
+        for (&callee, chan_api) in chan_apis {
+            if chan_api.operation() != kind {
+                continue;
+            }
+            let callers: Vec<InstanceId> = callgraph.graph.neighbors_directed(callee, Incoming).collect();
+            for caller in callers {
+                let inst = callgraph.index_to_instance(caller).unwrap();
+                let body = self.tcx.instance_mir(inst.instance().def);
+                let Some(callsites) = callgraph.callsites(caller, callee) else {
+                    continue;
+                };
+                for callsite in callsites {
+                    if let Some(location) = callsite.location() {
+                        if let TerminatorKind::Call { args, .. } = &body[location.block].terminator().kind {
+                            // `Sender::send(&self, ..)`/`Receiver::recv(&self)`: the
+                            // endpoint itself is always the first argument.
+                            if let Some(place) = args.first().and_then(|arg| arg.place()) {
+                                out.push((caller, location, place.local));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Follow an `Arc`/`Rc` clone chain backwards from `local` to the local
+    /// it was cloned from, one hop at a time (the caller loops to fixpoint).
+    fn clone_source(&self, body: &rustc_middle::mir::Body<'tcx>, local: Local) -> Option<Local> {
+        for block in body.basic_blocks.indices() {
+            if let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &body[block].terminator().kind
+            {
+                if destination.local != local {
+                    continue;
+                }
+                if let Some((def_id, generic_args)) = func.const_fn_def() {
+                    if is_arc_or_rc_clone(def_id, generic_args, self.tcx) {
+                        return args.first().and_then(|arg| arg.place()).map(|place| place.local);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Every `(caller instance, argument place local)` a parameter `local`
+    /// of `instance_id` could have been passed in as, across every call
+    /// site into `instance_id` -- the cross-function counterpart to
+    /// `clone_source`'s intra-function clone-chain walk. MIR binds a
+    /// body's own parameters to locals `_1..=_{arg_count}`; a `local`
+    /// outside that range was created inside `instance_id` itself and has
+    /// no caller-side place to resolve back to, so this returns nothing
+    /// for it.
+    fn caller_arguments(
+        &self,
+        callgraph: &CallGraph<'tcx>,
+        instance_id: InstanceId,
+        body: &rustc_middle::mir::Body<'tcx>,
+        local: Local,
+    ) -> Vec<(InstanceId, Local)> {
+        if local.index() == 0 || local.index() > body.arg_count {
+            return Vec::new();
+        }
+        let param_index = local.index() - 1;
+        let mut out = Vec::new();
+        for caller in callgraph.graph.neighbors_directed(instance_id, Incoming) {
+            let Some(callsites) = callgraph.callsites(caller, instance_id) else { continue };
+            let caller_inst = callgraph.index_to_instance(caller).unwrap();
+            let caller_body = self.tcx.instance_mir(caller_inst.instance().def);
+            for callsite in callsites {
+                let Some(location) = callsite.location() else { continue };
+                if let TerminatorKind::Call { args, .. } = &caller_body[location.block].terminator().kind {
+                    if let Some(place) = args.get(param_index).and_then(|arg| arg.place()) {
+                        out.push((caller, place.local));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Resolve a call-site local back to the `ReceiverId` it was bound to
+    /// at its `channel()` call, following `Arc`/`Rc` clones and plain moves
+    /// -- within `instance_id`'s own body, and across call boundaries when
+    /// `local` turns out to be a parameter the receiver was only passed
+    /// into this function through (see `caller_arguments`). `visited`
+    /// guards against revisiting the same `(instance, local)` pair through
+    /// a callgraph cycle.
+    fn resolve_receiver(
+        &self,
+        alias_analysis: &mut AliasAnalysis<'_, 'tcx>,
+        chan_sites: &[(SenderId, ReceiverId)],
+        callgraph: &CallGraph<'tcx>,
+        instance_id: InstanceId,
+        body: &rustc_middle::mir::Body<'tcx>,
+        local: Local,
+    ) -> Option<ReceiverId> {
+        let mut visited = FxHashSet::default();
+        self.resolve_receiver_rec(alias_analysis, chan_sites, callgraph, instance_id, body, local, &mut visited)
+    }
+
+    fn resolve_receiver_rec(
+        &self,
+        alias_analysis: &mut AliasAnalysis<'_, 'tcx>,
+        chan_sites: &[(SenderId, ReceiverId)],
+        callgraph: &CallGraph<'tcx>,
+        instance_id: InstanceId,
+        body: &rustc_middle::mir::Body<'tcx>,
+        mut local: Local,
+        visited: &mut FxHashSet<(InstanceId, Local)>,
+    ) -> Option<ReceiverId> {
+        loop {
+            if !visited.insert((instance_id, local)) {
+                return None;
+            }
+            if let Some(recv) = chan_sites.iter().map(|(_, r)| r).find(|recv| {
+                recv.instance_id == instance_id
+                    && (recv.local == local || alias_analysis.may_alias(instance_id, recv.local, local))
+            }) {
+                return Some(*recv);
+            }
+            if let Some(next) = self.clone_source(body, local) {
+                local = next;
+                continue;
+            }
+            for (caller_instance, caller_local) in self.caller_arguments(callgraph, instance_id, body, local) {
+                let caller_inst = callgraph.index_to_instance(caller_instance).unwrap();
+                let caller_body = self.tcx.instance_mir(caller_inst.instance().def);
+                if let Some(recv) =
+                    self.resolve_receiver_rec(alias_analysis, chan_sites, callgraph, caller_instance, caller_body, caller_local, visited)
+                {
+                    return Some(recv);
+                }
+            }
+            return None;
+        }
+    }
+
+    /// Resolve a call-site local back to the `SenderId` it was bound to at
+    /// its `channel()` call, following `Arc`/`Rc` clones and plain moves --
+    /// within `instance_id`'s own body, and across call boundaries the same
+    /// way `resolve_receiver` does.
+    fn resolve_sender(
+        &self,
+        alias_analysis: &mut AliasAnalysis<'_, 'tcx>,
+        chan_sites: &[(SenderId, ReceiverId)],
+        callgraph: &CallGraph<'tcx>,
+        instance_id: InstanceId,
+        body: &rustc_middle::mir::Body<'tcx>,
+        local: Local,
+    ) -> Option<SenderId> {
+        let mut visited = FxHashSet::default();
+        self.resolve_sender_rec(alias_analysis, chan_sites, callgraph, instance_id, body, local, &mut visited)
+    }
+
+    fn resolve_sender_rec(
+        &self,
+        alias_analysis: &mut AliasAnalysis<'_, 'tcx>,
+        chan_sites: &[(SenderId, ReceiverId)],
+        callgraph: &CallGraph<'tcx>,
+        instance_id: InstanceId,
+        body: &rustc_middle::mir::Body<'tcx>,
+        mut local: Local,
+        visited: &mut FxHashSet<(InstanceId, Local)>,
+    ) -> Option<SenderId> {
+        loop {
+            if !visited.insert((instance_id, local)) {
+                return None;
+            }
+            if let Some(sender) = chan_sites.iter().map(|(s, _)| s).find(|sender| {
+                sender.instance_id == instance_id
+                    && (sender.local == local || alias_analysis.may_alias(instance_id, sender.local, local))
+            }) {
+                return Some(*sender);
+            }
+            if let Some(next) = self.clone_source(body, local) {
+                local = next;
+                continue;
+            }
+            for (caller_instance, caller_local) in self.caller_arguments(callgraph, instance_id, body, local) {
+                let caller_inst = callgraph.index_to_instance(caller_instance).unwrap();
+                let caller_body = self.tcx.instance_mir(caller_inst.instance().def);
+                if let Some(sender) =
+                    self.resolve_sender_rec(alias_analysis, chan_sites, callgraph, caller_instance, caller_body, caller_local, visited)
+                {
+                    return Some(sender);
+                }
+            }
+            return None;
+        }
+    }
+
+    pub fn detect<'a>(
+        &mut self,
         callgraph: &'a CallGraph<'tcx>,
-        alias_analysis: &mut AliasAnalysis<'a, 'tcx>,) -> Vec<()> {
+        alias_analysis: &mut AliasAnalysis<'a, 'tcx>,
+    ) -> Vec<BlockingSend> {
         let chan_apis = self.collect_chan(callgraph);
-        println!("{:#?}", chan_apis);
-        // Init `worklist` with all the `InstanceId`s
-        let mut worklist = callgraph
-            .graph
-            .node_references()
-            .map(|(instance_id, _)| instance_id)
-            .collect::<VecDeque<_>>();
-        while let Some(node) = worklist.pop_front() {
-            // body = mir_body(node)
-            // for term in body.term
-            //   if term calls create
-            //      record return value: rx, tx
-            //   else if 
-            //      record return value: rx, tx
-            
+        let chan_sites = self.bind_chan_sites(callgraph, &chan_apis);
+
+        // `live_out[instance_id]`: receivers still live when control leaves
+        // `instance_id`, i.e. the dataflow fact propagated to its callers.
+        // A receiver is live from its creation site onward and killed where
+        // its local (or a clone of it) hits `StorageDead` or a `Drop`;
+        // `live_out` only grows (by union) over a finite set of receivers,
+        // so the worklist below always reaches a fixpoint.
+        let mut live_out: FxHashMap<InstanceId, LiveReceivers> = FxHashMap::default();
+        let mut worklist: VecDeque<InstanceId> =
+            callgraph.graph.node_references().map(|(id, _)| id).collect();
+
+        while let Some(instance_id) = worklist.pop_front() {
+            let inst = callgraph.index_to_instance(instance_id).unwrap();
+            let body = self.tcx.instance_mir(inst.instance().def);
+
+            let mut live = LiveReceivers::default();
+            for (_, recv) in chan_sites.iter().filter(|(_, r)| r.instance_id == instance_id) {
+                live.insert(*recv);
+            }
+            // A receiver still live on exit from a callee (created there and
+            // returned/stored, say) is live here too.
+            for callee in callgraph.graph.neighbors_directed(instance_id, Outgoing) {
+                if let Some(callee_live) = live_out.get(&callee) {
+                    live.union_in_place(callee_live.clone());
+                }
+            }
+
+            for block in body.basic_blocks.indices() {
+                let data = &body[block];
+                for statement in &data.statements {
+                    if let StatementKind::StorageDead(local) = statement.kind {
+                        if let Some(recv) =
+                            self.resolve_receiver(alias_analysis, &chan_sites, callgraph, instance_id, body, local)
+                        {
+                            let mut dead = LiveReceivers::default();
+                            dead.insert(recv);
+                            live.difference_in_place(&dead);
+                        }
+                    }
+                }
+                if let TerminatorKind::Drop { place, .. } = &data.terminator().kind {
+                    if let Some(recv) =
+                        self.resolve_receiver(alias_analysis, &chan_sites, callgraph, instance_id, body, place.local)
+                    {
+                        let mut dead = LiveReceivers::default();
+                        dead.insert(recv);
+                        live.difference_in_place(&dead);
+                    }
+                }
+            }
+
+            let changed = live_out.entry(instance_id).or_default().union_in_place(live);
+            if changed {
+                worklist.extend(callgraph.graph.neighbors_directed(instance_id, Incoming));
+            }
+        }
+
+        // A send is a guaranteed-blocking bug if, by the time it executes,
+        // its matching receiver is provably already dead -- not "dead
+        // somewhere in the function", but dead at a point dominance
+        // guarantees runs before this particular send.
+        let mut findings = Vec::new();
+        for (instance_id, location, local) in
+            self.call_sites_of(callgraph, &chan_apis, ChanOperation::Send)
+        {
+            let inst = callgraph.index_to_instance(instance_id).unwrap();
+            let body = self.tcx.instance_mir(inst.instance().def);
+            let Some(sender) = self.resolve_sender(alias_analysis, &chan_sites, callgraph, instance_id, body, local)
+            else {
+                continue;
+            };
+            let Some(receiver) = chan_sites.iter().find(|(s, _)| *s == sender).map(|(_, r)| *r) else {
+                continue;
+            };
+            let dominators = body.basic_blocks.dominators();
+            let killed = self.receiver_killed_before(
+                alias_analysis,
+                &chan_sites,
+                callgraph,
+                &live_out,
+                instance_id,
+                body,
+                &dominators,
+                receiver,
+                location,
+            );
+            if killed {
+                findings.push(BlockingSend { sender, send_at: location });
+            }
+        }
+        findings
+    }
+
+    /// Whether `receiver` is provably dead by the time control reaches
+    /// `before_loc` in `body` -- i.e. some `StorageDead`/`Drop` of a local
+    /// resolving to it (or a call handing it off to a callee that's already
+    /// dropped it) sits at a location dominance guarantees runs first.
+    /// Unlike scanning the whole function for any kill, a drop that merely
+    /// sits *after* `before_loc` in program order (the ordinary, correct
+    /// "send, then later drop the receiver" shutdown pattern) is never
+    /// treated as killing it here.
+    fn receiver_killed_before(
+        &self,
+        alias_analysis: &mut AliasAnalysis<'_, 'tcx>,
+        chan_sites: &[(SenderId, ReceiverId)],
+        callgraph: &CallGraph<'tcx>,
+        live_out: &FxHashMap<InstanceId, LiveReceivers>,
+        instance_id: InstanceId,
+        body: &rustc_middle::mir::Body<'tcx>,
+        dominators: &Dominators<BasicBlock>,
+        receiver: ReceiverId,
+        before_loc: Location,
+    ) -> bool {
+        let dominates_before = |block: BasicBlock| block != before_loc.block && dominators.dominates(block, before_loc.block);
+
+        for block in body.basic_blocks.indices() {
+            if !dominates_before(block) {
+                continue;
+            }
+            let data = &body[block];
+            for statement in &data.statements {
+                if let StatementKind::StorageDead(local) = statement.kind {
+                    if self.resolve_receiver(alias_analysis, chan_sites, callgraph, instance_id, body, local) == Some(receiver) {
+                        return true;
+                    }
+                }
+            }
+            if let TerminatorKind::Drop { place, .. } = &data.terminator().kind {
+                if self.resolve_receiver(alias_analysis, chan_sites, callgraph, instance_id, body, place.local) == Some(receiver) {
+                    return true;
+                }
+            }
+        }
+
+        // A call dominating `before_loc` that's itself handed `receiver` by
+        // value (resolved the same way `resolve_receiver` resolves any other
+        // local) kills it here too, unless the callee's `live_out` still
+        // holds it. A borrowed receiver (`rx.recv()`'s `&rx`) is left with
+        // the caller and never counts; a callee that stores a receiver it was
+        // handed, rather than created, is treated as having dropped it.
+        for callee in callgraph.graph.neighbors_directed(instance_id, Outgoing) {
+            let callee_still_live = live_out.get(&callee).is_some_and(|live| live.raw_receiver_ids().contains(&receiver));
+            if callee_still_live {
+                continue;
+            }
+            let Some(callsites) = callgraph.callsites(instance_id, callee) else { continue };
+            for callsite in callsites {
+                let Some(location) = callsite.location() else { continue };
+                if !dominates_before(location.block) {
+                    continue;
+                }
+                let TerminatorKind::Call { args, .. } = &body[location.block].terminator().kind else { continue };
+                let takes_receiver = args.iter().any(|arg| match arg {
+                    rustc_middle::mir::Operand::Move(place) if !body.local_decls[place.local].ty.is_ref() => {
+                        self.resolve_receiver(alias_analysis, chan_sites, callgraph, instance_id, body, place.local)
+                            == Some(receiver)
+                    }
+                    _ => false,
+                });
+                if takes_receiver {
+                    return true;
+                }
+            }
         }
-        vec![]
+        false
     }
 }
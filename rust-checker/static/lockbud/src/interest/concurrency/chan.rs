@@ -1,44 +1,272 @@
-//! Denotes chan APIs in std (crossbeam).
+//! Denotes chan APIs across the ecosystem's most common channel crates:
+//! `std::sync::mpsc`, `crossbeam_channel`, `tokio::sync::mpsc`, and
+//! `futures::channel::mpsc`.
 //!
 //! 1. create mpsc: std::sync::mpsc::channel::<T>() -> (std::sync::mpsc::Sender<T>, std::sync::mpsc::Receiver<T>)
 //! 2. send to mpsc: std::sync::mpsc::Sender::<T>::send(move &std::sync::mpsc::Sender<T>, T) -> std::result::Result<(), std::sync::mpmc::SendError<i32>>;
 //! 3. recv from mpsc: std::sync::mpsc::Receiver::<T>::recv(&std::sync::mpsc::Receiver<T>) -> std::result::Result<(), std::sync::mpmc::SendError<i32>>;
-extern crate rustc_hash;
 extern crate rustc_middle;
 
 use rustc_middle::ty::{Instance, TyCtxt};
-use rustc_hash::{FxHashMap, FxHashSet};
+
+/// What kind of channel operation a call site performs, normalized across
+/// every crate `ChanApi` recognizes. Downstream detectors match on this
+/// instead of re-deriving per-crate semantics for every family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChanOperation {
+    Create,
+    Send,
+    TrySend,
+    SendTimeout,
+    Recv,
+    TryRecv,
+    /// crossbeam's `select!`/`Select::select`: waits on whichever of
+    /// several endpoints becomes ready first.
+    Select,
+}
+
+/// Whether a channel's capacity is known to be bounded or unbounded.
+/// `Unknown` is only returned for a non-`Create` call site, where capacity
+/// isn't something that particular operation can tell us.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capacity {
+    Bounded,
+    Unbounded,
+    Unknown,
+}
 
 #[derive(Clone, Copy, Debug)]
 pub enum ChanApi {
     Std(StdMpscChanApi),
+    Crossbeam(CrossbeamChanApi),
+    Tokio(TokioChanApi),
+    Futures(FuturesChanApi),
+}
+
+impl ChanApi {
+    /// Normalized operation, regardless of which crate this call site came from.
+    pub fn operation(&self) -> ChanOperation {
+        match self {
+            ChanApi::Std(api) => api.operation(),
+            ChanApi::Crossbeam(api) => api.operation(),
+            ChanApi::Tokio(api) => api.operation(),
+            ChanApi::Futures(api) => api.operation(),
+        }
+    }
+
+    /// Whether this operation can park/await the caller instead of
+    /// returning immediately, i.e. whether a dead or full channel actually
+    /// blocks forward progress here instead of just erroring out.
+    pub fn is_blocking(&self) -> bool {
+        matches!(
+            self.operation(),
+            ChanOperation::Send | ChanOperation::Recv | ChanOperation::SendTimeout | ChanOperation::Select
+        )
+    }
+
+    /// Capacity of the channel this call site constructs; `Capacity::Unknown`
+    /// for every operation other than `Create`.
+    pub fn capacity(&self) -> Capacity {
+        match self {
+            ChanApi::Std(StdMpscChanApi::Create { bounded }) => *bounded,
+            ChanApi::Crossbeam(CrossbeamChanApi::Create { bounded }) => *bounded,
+            ChanApi::Tokio(TokioChanApi::Create { bounded }) => *bounded,
+            ChanApi::Futures(FuturesChanApi::Create { bounded }) => *bounded,
+            _ => Capacity::Unknown,
+        }
+    }
+
+    pub fn from_instance<'tcx>(instance: &Instance<'tcx>, tcx: TyCtxt<'tcx>) -> Option<Self> {
+        let path = tcx.def_path_str_with_args(instance.def_id(), instance.args);
+        StdMpscChanApi::from_path(&path)
+            .map(ChanApi::Std)
+            .or_else(|| CrossbeamChanApi::from_path(&path).map(ChanApi::Crossbeam))
+            .or_else(|| TokioChanApi::from_path(&path).map(ChanApi::Tokio))
+            .or_else(|| FuturesChanApi::from_path(&path).map(ChanApi::Futures))
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum StdMpscChanApi {
-    Create,
+    /// `channel()` is unbounded; `sync_channel(bound)` is bounded.
+    Create { bounded: Capacity },
     Send,
     Recv,
 }
 
-impl ChanApi {
-    pub fn from_instance<'tcx>(instance: &Instance<'tcx>, tcx: TyCtxt<'tcx>) -> Option<Self> {
-        let path = tcx.def_path_str_with_args(instance.def_id(), instance.args);
-        let std_mpsc_chan = "std::sync::mpsc::";
-        if path.starts_with(std_mpsc_chan) {
-            let tail = &path.as_bytes()[std_mpsc_chan.len()..];
-            let std_mpsc_chan_api = if tail.starts_with("channel::".as_bytes()) {
-                Some(ChanApi::Std(StdMpscChanApi::Create))
-            } else if tail.starts_with("Sender::".as_bytes()) && tail.ends_with("send".as_bytes()) {
-                Some(ChanApi::Std(StdMpscChanApi::Send))
-            } else if tail.starts_with("Receiver::".as_bytes()) && tail.ends_with("recv".as_bytes()) {
-                Some(ChanApi::Std(StdMpscChanApi::Recv))
-            } else {
-                None
-            };
-            std_mpsc_chan_api
+impl StdMpscChanApi {
+    fn operation(&self) -> ChanOperation {
+        match self {
+            StdMpscChanApi::Create { .. } => ChanOperation::Create,
+            StdMpscChanApi::Send => ChanOperation::Send,
+            StdMpscChanApi::Recv => ChanOperation::Recv,
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        let prefix = "std::sync::mpsc::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"channel::") {
+            Some(StdMpscChanApi::Create { bounded: Capacity::Unbounded })
+        } else if tail.starts_with(b"sync_channel::") {
+            Some(StdMpscChanApi::Create { bounded: Capacity::Bounded })
+        } else if (tail.starts_with(b"Sender::") || tail.starts_with(b"SyncSender::")) && tail.ends_with(b"send") {
+            Some(StdMpscChanApi::Send)
+        } else if tail.starts_with(b"Receiver::") && tail.ends_with(b"recv") {
+            Some(StdMpscChanApi::Recv)
         } else {
             None
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum CrossbeamChanApi {
+    /// `unbounded()` has no capacity; `bounded(cap)` does.
+    Create { bounded: Capacity },
+    Send,
+    TrySend,
+    SendTimeout,
+    Recv,
+    TryRecv,
+    Select,
+}
+
+impl CrossbeamChanApi {
+    fn operation(&self) -> ChanOperation {
+        match self {
+            CrossbeamChanApi::Create { .. } => ChanOperation::Create,
+            CrossbeamChanApi::Send => ChanOperation::Send,
+            CrossbeamChanApi::TrySend => ChanOperation::TrySend,
+            CrossbeamChanApi::SendTimeout => ChanOperation::SendTimeout,
+            CrossbeamChanApi::Recv => ChanOperation::Recv,
+            CrossbeamChanApi::TryRecv => ChanOperation::TryRecv,
+            CrossbeamChanApi::Select => ChanOperation::Select,
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        let prefix = "crossbeam_channel::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"unbounded::") {
+            Some(CrossbeamChanApi::Create { bounded: Capacity::Unbounded })
+        } else if tail.starts_with(b"bounded::") {
+            Some(CrossbeamChanApi::Create { bounded: Capacity::Bounded })
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"send_timeout") {
+            Some(CrossbeamChanApi::SendTimeout)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"try_send") {
+            Some(CrossbeamChanApi::TrySend)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"send") {
+            Some(CrossbeamChanApi::Send)
+        } else if tail.starts_with(b"Receiver::") && tail.ends_with(b"try_recv") {
+            Some(CrossbeamChanApi::TryRecv)
+        } else if tail.starts_with(b"Receiver::") && tail.ends_with(b"recv") {
+            Some(CrossbeamChanApi::Recv)
+        } else if tail.starts_with(b"Select::") {
+            Some(CrossbeamChanApi::Select)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TokioChanApi {
+    /// `channel(capacity)` is bounded; `unbounded_channel()` is unbounded.
+    Create { bounded: Capacity },
+    Send,
+    TrySend,
+    Recv,
+    TryRecv,
+}
+
+impl TokioChanApi {
+    fn operation(&self) -> ChanOperation {
+        match self {
+            TokioChanApi::Create { .. } => ChanOperation::Create,
+            TokioChanApi::Send => ChanOperation::Send,
+            TokioChanApi::TrySend => ChanOperation::TrySend,
+            TokioChanApi::Recv => ChanOperation::Recv,
+            TokioChanApi::TryRecv => ChanOperation::TryRecv,
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        let prefix = "tokio::sync::mpsc::";
+        if !path.starts_with(prefix) {
+            return None;
+        }
+        let tail = &path.as_bytes()[prefix.len()..];
+        if tail.starts_with(b"channel::") {
+            Some(TokioChanApi::Create { bounded: Capacity::Bounded })
+        } else if tail.starts_with(b"unbounded_channel::") {
+            Some(TokioChanApi::Create { bounded: Capacity::Unbounded })
+        } else if tail.starts_with(b"UnboundedSender::") && tail.ends_with(b"send") {
+            // Unlike the bounded `Sender::send`, an unbounded channel can't
+            // be backpressured, so this resolves immediately just like a
+            // `try_send` rather than awaiting capacity.
+            Some(TokioChanApi::TrySend)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"try_send") {
+            Some(TokioChanApi::TrySend)
+        } else if tail.starts_with(b"Sender::") && (tail.ends_with(b"send") || tail.ends_with(b"blocking_send")) {
+            Some(TokioChanApi::Send)
+        } else if (tail.starts_with(b"Receiver::") || tail.starts_with(b"UnboundedReceiver::")) && tail.ends_with(b"try_recv") {
+            Some(TokioChanApi::TryRecv)
+        } else if (tail.starts_with(b"Receiver::") || tail.starts_with(b"UnboundedReceiver::")) && tail.ends_with(b"recv") {
+            Some(TokioChanApi::Recv)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FuturesChanApi {
+    /// `channel(capacity)` is bounded; `unbounded()` is unbounded.
+    Create { bounded: Capacity },
+    Send,
+    TrySend,
+}
+
+impl FuturesChanApi {
+    fn operation(&self) -> ChanOperation {
+        match self {
+            FuturesChanApi::Create { .. } => ChanOperation::Create,
+            FuturesChanApi::Send => ChanOperation::Send,
+            FuturesChanApi::TrySend => ChanOperation::TrySend,
+        }
+    }
+
+    /// Only construction and the send side are matched: `Receiver` is
+    /// consumed through the generic `Stream`/`StreamExt` trait
+    /// (`.next()`/`.try_next()`), not a dedicated `recv`/`try_recv` method,
+    /// so there's no stable per-crate `def_path_str` to match the way
+    /// there is for the other three families.
+    fn from_path(path: &str) -> Option<Self> {
+        // `futures::channel::mpsc` re-exports `futures_channel::mpsc`, and
+        // which path rustc reports depends on which the caller named.
+        let tail = path
+            .strip_prefix("futures_channel::mpsc::")
+            .or_else(|| path.strip_prefix("futures::channel::mpsc::"))?
+            .as_bytes();
+        if tail.starts_with(b"channel::") {
+            Some(FuturesChanApi::Create { bounded: Capacity::Bounded })
+        } else if tail.starts_with(b"unbounded::") {
+            Some(FuturesChanApi::Create { bounded: Capacity::Unbounded })
+        } else if tail.starts_with(b"UnboundedSender::") && tail.ends_with(b"unbounded_send") {
+            Some(FuturesChanApi::TrySend)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"try_send") {
+            Some(FuturesChanApi::TrySend)
+        } else if tail.starts_with(b"Sender::") && tail.ends_with(b"send") {
+            Some(FuturesChanApi::Send)
+        } else {
+            None
+        }
+    }
+}
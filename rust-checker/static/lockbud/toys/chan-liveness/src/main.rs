@@ -0,0 +1,29 @@
+// A fixture for `ChanDetector`/`BlockingSend`, not `ChannelDeadlockDetector`
+// (the bug `channel-deadlock` next door exercises).
+//
+// `send_after_drop` sends on `tx` after its matching `rx` has already been
+// dropped earlier in the *same* function -- dominance guarantees the drop
+// runs first, so this send can never be received and must be reported.
+//
+// `send_then_drop` is the ordinary, correct shutdown shape: send first, drop
+// the receiver afterwards. A flow-insensitive liveness check that scans the
+// whole function for any drop of `rx2` (ignoring that the drop comes after
+// the send) would wrongly flag this send too.
+use std::sync::mpsc::channel;
+
+fn send_after_drop() {
+    let (tx, rx) = channel();
+    drop(rx);
+    let _ = tx.send(1); // expect: BlockingSend
+}
+
+fn send_then_drop() {
+    let (tx2, rx2) = channel();
+    let _ = tx2.send(1);
+    drop(rx2);
+}
+
+fn main() {
+    send_after_drop();
+    send_then_drop();
+}